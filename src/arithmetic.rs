@@ -0,0 +1,175 @@
+use std::rc::Rc;
+
+use crate::builtins::check_conditional;
+use crate::error::StaplError;
+use crate::eval::{Call, CallStack, Env};
+use crate::lex::{Expectation, LexItem};
+use crate::number::Number;
+
+impl CallStack {
+    pub(crate) fn create_arithmetic_words(self: &mut Self) -> () {
+        let binary = vec![Expectation::Num, Expectation::Num];
+        self.add_builtin("+", binary.clone(), Rc::new(action_add));
+        self.add_builtin("-", binary.clone(), Rc::new(action_subtract));
+        self.add_builtin("*", binary.clone(), Rc::new(action_multiply));
+        self.add_builtin("/", binary.clone(), Rc::new(action_divide));
+        self.add_builtin("mod", binary.clone(), Rc::new(action_modulo));
+        self.add_builtin("min", binary.clone(), Rc::new(action_min));
+        self.add_builtin("max", binary.clone(), Rc::new(action_max));
+        self.add_builtin("neg", vec![Expectation::Num], Rc::new(action_negate));
+        self.add_builtin("abs", vec![Expectation::Num], Rc::new(action_abs));
+
+        // = and != compare any two values, the ordering words only numbers
+        let values = vec![Expectation::NumStaLit, Expectation::NumStaLit];
+        self.add_builtin("=", values.clone(), Rc::new(action_equal));
+        self.add_builtin("!=", values.clone(), Rc::new(action_not_equal));
+        self.add_builtin("<", binary.clone(), Rc::new(action_less));
+        self.add_builtin("<=", binary.clone(), Rc::new(action_less_equal));
+        self.add_builtin(">", binary.clone(), Rc::new(action_greater));
+        self.add_builtin(">=", binary.clone(), Rc::new(action_greater_equal));
+
+        self.add_builtin("and", values.clone(), Rc::new(action_and));
+        self.add_builtin("or", values.clone(), Rc::new(action_or));
+        self.add_builtin("not", vec![Expectation::NumStaLit], Rc::new(action_not));
+    }
+}
+
+// pushes a numeric result, a float that ran off to infinity is reported as an overflow
+fn push_number(call: &mut Call, result: Number) -> Result<(), StaplError> {
+    if let Number::Float(f) = result {
+        if !f.is_finite() {
+            return Err(StaplError::Overflow {
+                word: call.name.to_string(),
+                span: call.span().clone(),
+            });
+        }
+    }
+    call.push_result(LexItem::Num(result, call.span().clone()));
+    return Ok(());
+}
+pub(crate) fn push_truth(call: &mut Call, truth: bool) -> () {
+    call.push_result(LexItem::Bool(truth, call.span().clone()));
+}
+// None from the exact division operations means the divisor was zero
+fn check_divisor(call: &Call, result: Option<Number>) -> Result<Number, StaplError> {
+    match result {
+        Some(n) => {
+            return Ok(n);
+        }
+        None => {
+            return Err(StaplError::DivisionByZero {
+                word: call.name.to_string(),
+                span: call.span().clone(),
+            });
+        }
+    }
+}
+
+pub(crate) fn action_add(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    return push_number(call, a.add(&b));
+}
+pub(crate) fn action_subtract(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    return push_number(call, a.sub(&b));
+}
+pub(crate) fn action_multiply(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    return push_number(call, a.mul(&b));
+}
+// exact: integers that don't divide evenly give a rational
+pub(crate) fn action_divide(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    let result = check_divisor(call, a.div(&b))?;
+    return push_number(call, result);
+}
+// the remainder takes the sign of the dividend
+pub(crate) fn action_modulo(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    let result = check_divisor(call, a.rem(&b))?;
+    return push_number(call, result);
+}
+pub(crate) fn action_min(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    if b < a {
+        return push_number(call, b);
+    }
+    return push_number(call, a);
+}
+pub(crate) fn action_max(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    if b > a {
+        return push_number(call, b);
+    }
+    return push_number(call, a);
+}
+pub(crate) fn action_negate(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    return push_number(call, a.neg());
+}
+pub(crate) fn action_abs(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    return push_number(call, a.abs());
+}
+
+pub(crate) fn action_equal(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_argument()?;
+    let b = call.pop_argument()?;
+    push_truth(call, a == b);
+    return Ok(());
+}
+pub(crate) fn action_not_equal(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_argument()?;
+    let b = call.pop_argument()?;
+    push_truth(call, a != b);
+    return Ok(());
+}
+pub(crate) fn action_less(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    push_truth(call, a < b);
+    return Ok(());
+}
+pub(crate) fn action_less_equal(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    push_truth(call, a <= b);
+    return Ok(());
+}
+pub(crate) fn action_greater(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    push_truth(call, a > b);
+    return Ok(());
+}
+pub(crate) fn action_greater_equal(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_num()?;
+    let b = call.pop_num()?;
+    push_truth(call, a >= b);
+    return Ok(());
+}
+
+pub(crate) fn action_and(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_argument()?;
+    let b = call.pop_argument()?;
+    push_truth(call, check_conditional(a) && check_conditional(b));
+    return Ok(());
+}
+pub(crate) fn action_or(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_argument()?;
+    let b = call.pop_argument()?;
+    push_truth(call, check_conditional(a) || check_conditional(b));
+    return Ok(());
+}
+pub(crate) fn action_not(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_argument()?;
+    push_truth(call, !check_conditional(a));
+    return Ok(());
+}
@@ -0,0 +1,447 @@
+use std::rc::Rc;
+
+use crate::error::StaplError;
+use crate::eval::{Action, Call, CallStack, Env, Parameter, Word};
+use crate::format::format_source;
+use crate::lex::{free_names, if_branches, is_positional, Expectation, LexItem};
+use crate::maps::{map_entries, map_items};
+
+impl CallStack {
+    // expectations are listed last argument first: the last one is matched first
+    pub(crate) fn add_builtin(
+        self: &mut Self,
+        name: &str,
+        expectations: Vec<Expectation>,
+        action: Action,
+    ) -> () {
+        let word = Word {
+            name: name.to_string(),
+            arity: expectations.len(),
+            action: action,
+            substitution: None,
+            expectations: expectations,
+        };
+        self.words.insert(name.to_string(), word);
+    }
+
+    pub(crate) fn create_builtin_words(self: &mut Self) -> () {
+        let string2 = String::from("if");
+        let ifword = Word {
+            name: "if".to_string(),
+            arity: 3,
+            action: Rc::new(action_if),
+            substitution: None,
+            // the branches are evaluated as arguments unless they are lambdas,
+            // a lambda is only run if it is chosen. the condition can be any value
+            expectations: vec![
+                Expectation::Value,
+                Expectation::Value,
+                Expectation::NumStaLit,
+            ],
+        };
+        self.words.insert(string2, ifword);
+
+        let defword = Word {
+            name: "define".to_string(),
+            arity: 1,
+            action: Rc::new(action_define),
+            substitution: None,
+            expectations: vec![Expectation::Any, Expectation::Word],
+        };
+        self.words.insert("define".to_string(), defword);
+
+        let defextract = Word {
+            name: "#".to_string(),
+            arity: 2,
+            action: Rc::new(action_extract), // change this to an option?
+            substitution: None,
+            expectations: vec![Expectation::Stack, Expectation::Num],
+        };
+        self.words.insert("#".to_string(), defextract);
+
+        let deftake = Word {
+            name: "take".to_string(),
+            arity: 1,
+            action: Rc::new(action_take),
+            substitution: None,
+            expectations: vec![Expectation::Num],
+        };
+        self.words.insert("take".to_string(), deftake);
+
+        let defprint = Word {
+            name: "print".to_string(),
+            arity: 1,
+            action: Rc::new(action_print),
+            substitution: None,
+            expectations: vec![Expectation::NumStaLit],
+        };
+        self.words.insert("print".to_string(), defprint);
+
+        self.add_builtin(
+            "let",
+            vec![
+                Expectation::Lambda,
+                Expectation::Value,
+                Expectation::Parameter,
+            ],
+            Rc::new(action_let),
+        );
+
+        self.create_arithmetic_words();
+        self.create_string_words();
+        self.create_list_words();
+        self.create_map_words();
+        self.create_shuffle_words();
+    }
+}
+
+pub(crate) fn action_print(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let item = call.pop_argument()?;
+    println!("printing:{}", format_source(&item));
+    return Ok(());
+}
+pub(crate) fn action_define(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let first = call.pop_argument()?;
+    match first {
+        LexItem::Word(w, _) => {
+            let value = call.pop_argument()?;
+            let mut newword =
+                Word::make_word(w.to_string(), 0, Rc::new(action_substitution), Some(value));
+            newword.setup_expectations();
+            env.words.insert(w.to_string(), newword);
+        }
+        _ => {
+            return Err(call.type_mismatch(Expectation::Word, first));
+        }
+    }
+    return Ok(());
+}
+pub(crate) fn action_take(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    if !call.ready {
+        // first application: the count is known, ask for that many more arguments
+        let new_arity = call.pop_int()?;
+        if new_arity < 0 {
+            return Err(StaplError::NegativeCount {
+                word: call.name.to_string(),
+                count: new_arity,
+                span: call.span().clone(),
+            });
+        }
+        // the taken values end up on a stack, so a count over the item limit can't be met
+        env.check_items(new_arity as usize, call.span())?;
+        call.arity = new_arity as usize;
+        for i in 0..call.arity {
+            call.expectations.push(Expectation::NumStaLit);
+        }
+        call.ready = true;
+        if call.arity > 0 {
+            return Ok(());
+        }
+    }
+
+    let mut newstack = Vec::new();
+    for a in 0..call.arguments.len() {
+        newstack.insert(0, call.pop_argument()?);
+    }
+
+    call.results
+        .push(LexItem::Stack(newstack.to_owned(), call.span().clone()));
+    return Ok(());
+}
+pub(crate) fn action_extract(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let index = call.pop_int()?;
+    let s = call.pop_argument()?;
+    if let LexItem::Stack(s, _) = s {
+        if index > 0 && index <= s.len() as i64 {
+            call.results
+                .push(s[(s.len() as i64 - index) as usize].clone());
+        } else if index < 0 && index >= -(s.len() as i64) {
+            call.results.push(s[(-(index + 1)) as usize].clone());
+        } else {
+            return Err(StaplError::IndexOutOfRange {
+                word: call.name.to_string(),
+                index: index,
+                len: s.len(),
+                span: call.span().clone(),
+            });
+        }
+    } else {
+        return Err(call.type_mismatch(Expectation::Stack, s));
+    }
+    return Ok(());
+}
+// truthiness, used by if, and, or, not and filter:
+// false and nil are false, as are the number zero, the empty string and
+// empty stacks and maps. every other value is true, including literals and lambdas
+pub(crate) fn check_conditional(l: LexItem) -> bool {
+    match l {
+        LexItem::Bool(b, _) => {
+            return b;
+        }
+        LexItem::Nil(_) => {
+            return false;
+        }
+        LexItem::Num(n, _) => {
+            return !n.is_zero();
+        }
+        LexItem::Str(s, _) => {
+            return s.len() > 0;
+        }
+        LexItem::Stack(s, _) => {
+            return s.len() > 0;
+        }
+        LexItem::Map(m, _) => {
+            return m.len() > 0;
+        }
+        _ => {
+            return true;
+        }
+    }
+}
+pub(crate) fn action_if(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    //println!("action_if arguments {:?}", call.arguments);
+    let conditional = call.pop_argument()?;
+    let if_clause = call.pop_argument()?;
+
+    let else_clause = call.pop_argument()?;
+
+    if check_conditional(conditional) {
+        call.results.push(if_clause);
+    } else {
+        call.results.push(else_clause);
+    }
+    return Ok(());
+}
+
+pub(crate) fn parameter_reduce<'a>(a: &'a Parameter, l: &'a LexItem) -> Option<&'a LexItem> {
+    match l {
+        LexItem::Parameter(p, _) => {
+            if a.name == *p {
+                return Some(&a.value);
+            } else {
+                return None;
+            }
+        }
+        _ => {
+            return None;
+        }
+    }
+}
+
+// replaces parameters with their arguments. stacks and if branches are searched like the body,
+// a nested lambda only captures the named ones since its numbered parameters are its own.
+// the binder of a let and the lambdas after it keep the name it binds, and the
+// binder is renamed if an argument going under it has that name free
+pub(crate) fn beta_reduce(body: Vec<LexItem>, arguments: &Vec<Parameter>) -> Vec<LexItem> {
+    let mut betasub = Vec::new();
+    let mut shadowed: Vec<String> = Vec::new();
+    let mut renamed: Vec<Parameter> = Vec::new();
+    let mut after_let = false;
+    let branches = if_branches(&body);
+    // bodies are stored last item first, a binder is read in source order
+    let mut remaining: Vec<LexItem> = body;
+    while let Some(value) = remaining.pop() {
+        let binder = after_let;
+        let branch = branches.contains(&remaining.len());
+        after_let = value.is_word("let");
+        match value {
+            LexItem::Parameter(p, span) if binder => {
+                shadowed.push(p.clone());
+                let captures = arguments.iter().any(|a| free_names(&a.value).contains(&p));
+                if captures {
+                    let fresh = fresh_name(&p, &remaining, arguments);
+                    renamed.retain(|r| r.name != p);
+                    renamed.push(Parameter {
+                        name: p,
+                        value: LexItem::Parameter(fresh.clone(), span.clone()),
+                    });
+                    betasub.push(LexItem::Parameter(fresh, span));
+                } else {
+                    renamed.retain(|r| r.name != p);
+                    betasub.push(LexItem::Parameter(p, span));
+                }
+            }
+            // the branches of an if run in the call they are written in
+            LexItem::Lambda(s, span) if branch => {
+                let s = beta_reduce(s, &renamed);
+                let visible: Vec<Parameter> = arguments
+                    .iter()
+                    .filter(|a| !shadowed.contains(&a.name))
+                    .cloned()
+                    .collect();
+                betasub.push(LexItem::Lambda(beta_reduce(s, &visible), span));
+            }
+            LexItem::Lambda(s, span) => {
+                // a lambda that refers to the arguments closes over them instead of
+                // having them substituted, so it can leave the call it is written in
+                let s = beta_reduce(s, &renamed);
+                let captured: Vec<Parameter> = arguments
+                    .iter()
+                    .filter(|a| !is_positional(&a.name) && !shadowed.contains(&a.name))
+                    .filter(|a| mentions(&s, &a.name))
+                    .cloned()
+                    .collect();
+                if captured.len() == 0 {
+                    betasub.push(LexItem::Lambda(s, span));
+                } else {
+                    betasub.push(LexItem::Closure(s, captured, span));
+                }
+            }
+            LexItem::Stack(s, span) => {
+                let s = beta_reduce(s, &renamed);
+                let visible: Vec<Parameter> = arguments
+                    .iter()
+                    .filter(|a| !shadowed.contains(&a.name))
+                    .cloned()
+                    .collect();
+                betasub.push(LexItem::Stack(beta_reduce(s, &visible), span));
+            }
+            LexItem::Map(m, span) => {
+                let items = beta_reduce(map_items(&m), &renamed);
+                let visible: Vec<Parameter> = arguments
+                    .iter()
+                    .filter(|a| !shadowed.contains(&a.name))
+                    .cloned()
+                    .collect();
+                let items = beta_reduce(items, &visible);
+                // parameters are replaced one for one, so every key still has its value
+                betasub.push(LexItem::Map(map_entries(items).unwrap_or_default(), span));
+            }
+            value => {
+                let mut reduced = None;
+                for a in arguments.iter() {
+                    if let Some(p) = parameter_reduce(a, &value) {
+                        reduced = Some(p.clone());
+                        break;
+                    }
+                }
+                betasub.push(reduced.unwrap_or(value));
+            }
+        }
+    }
+    return betasub.into_iter().rev().collect();
+}
+
+// name_1, name_2.. until one is used neither in the rest of the body nor free in an argument
+fn fresh_name(name: &str, body: &Vec<LexItem>, arguments: &Vec<Parameter>) -> String {
+    let mut n = 1;
+    loop {
+        let fresh = format!("{}_{}", name, n);
+        let taken = mentions(body, &fresh)
+            || arguments
+                .iter()
+                .any(|a| a.name == fresh || free_names(&a.value).contains(&fresh));
+        if !taken {
+            return fresh;
+        }
+        n = n + 1;
+    }
+}
+
+fn mentions(items: &Vec<LexItem>, name: &str) -> bool {
+    for lexeme in items.iter() {
+        match lexeme {
+            LexItem::Parameter(p, _) => {
+                if p == name {
+                    return true;
+                }
+            }
+            LexItem::Lambda(s, _) | LexItem::Stack(s, _) => {
+                if mentions(s, name) {
+                    return true;
+                }
+            }
+            LexItem::Map(m, _) => {
+                if mentions(&map_items(m), name) {
+                    return true;
+                }
+            }
+            _ => {}
+        }
+    }
+    return false;
+}
+
+// arguments are bound as "1", "2".. in the order they arrive, this gives
+// each the name of the parameter in that position
+pub(crate) fn name_arguments(arguments: &Vec<Parameter>, names: &Vec<String>) -> Vec<Parameter> {
+    let mut named = Vec::new();
+    for a in arguments.iter() {
+        let mut name = a.name.to_string();
+        if let Ok(position) = a.name.parse::<usize>() {
+            if let Some(n) = names.get(position - 1) {
+                name = n.to_string();
+            }
+        }
+        named.push(Parameter {
+            name: name,
+            value: a.value.clone(),
+        });
+    }
+    return named;
+}
+
+// let $x value [ body ]: the body with x replaced by the value
+pub(crate) fn action_let(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let name = call.pop_argument()?;
+    let value = call.pop_argument()?;
+    let body = call.pop_argument()?;
+    let name = match name {
+        LexItem::Parameter(p, _) => p,
+        _ => {
+            return Err(call.type_mismatch(Expectation::Parameter, name));
+        }
+    };
+    match body {
+        LexItem::Lambda(..) | LexItem::Closure(..) => {
+            let binding = vec![Parameter {
+                name: name,
+                value: value,
+            }];
+            call.results = lambda_body(body, &binding);
+        }
+        _ => {
+            return Err(call.type_mismatch(Expectation::Lambda, body));
+        }
+    }
+    return Ok(());
+}
+
+// the body of a lambda or closure with its arguments, and a closure's captured
+// arguments, substituted in. anything else is its own body
+pub(crate) fn lambda_body(lambda: LexItem, arguments: &Vec<Parameter>) -> Vec<LexItem> {
+    match lambda {
+        LexItem::Lambda(s, _) => {
+            if arguments.len() == 0 {
+                return s;
+            }
+            return beta_reduce(s, arguments);
+        }
+        LexItem::Closure(s, captured, _) => {
+            let mut bindings = arguments.clone();
+            bindings.extend(captured);
+            return beta_reduce(s, &bindings);
+        }
+        value => {
+            return vec![value];
+        }
+    }
+}
+
+pub(crate) fn action_substitution(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let sub = call.substitution.take();
+    if sub.is_none() {
+        return Ok(());
+    }
+
+    //beta reduction
+    if call.arity > 0 {
+        let sub = sub.unwrap();
+        let names = sub.get_parameters().unwrap_or(Vec::new());
+        call.results = lambda_body(sub, &name_arguments(&call.arguments, &names));
+    } else {
+        call.results = lambda_body(sub.unwrap(), &Vec::new());
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,140 @@
+use crate::error::StaplError;
+use crate::eval::{load_source, pending, step, trace_finished, Pending};
+use crate::format::{
+    format_expstack, format_lexstack, format_parstack, format_source, format_values,
+};
+use crate::interpreter::{Interpreter, Value};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DebugCommand {
+    // one step: a single item pushed or a single call applied
+    Step,
+    // like step, but runs any call the step starts until it has been applied
+    Next,
+    // runs until the innermost pending call has been applied
+    Finish,
+    // runs until a breakpoint or the end
+    Continue,
+}
+
+#[derive(Debug, Clone)]
+pub enum DebugStop {
+    Paused,
+    Breakpoint(String),
+    Finished(Vec<Value>),
+}
+
+// the debugger drives eval one step at a time, stopping in front of the next
+// call to apply or item to push. errors end the session as they do in eval_str
+impl Interpreter {
+    pub fn debug_start(self: &mut Self, source: &str) -> Result<(), StaplError> {
+        if self.suspended {
+            self.abort();
+        }
+        self.start = self.program.ostack.len();
+        return load_source(&mut self.program, source.to_string());
+    }
+
+    pub fn debug_pending(self: &Self) -> Pending {
+        return pending(&self.program);
+    }
+
+    pub fn debug_run(self: &mut Self, command: DebugCommand) -> Result<DebugStop, StaplError> {
+        let depth = self.program.cstack.len();
+        let mut first = true;
+        loop {
+            if let Pending::Done = self.debug_pending() {
+                return self.debug_end();
+            }
+            if !first {
+                let stop = match command {
+                    DebugCommand::Step => true,
+                    DebugCommand::Next => self.program.cstack.len() <= depth,
+                    DebugCommand::Finish => depth > 0 && self.program.cstack.len() < depth,
+                    DebugCommand::Continue => false,
+                };
+                if stop {
+                    return Ok(DebugStop::Paused);
+                }
+                if let Some(word) = self.breakpoint_hit() {
+                    return Ok(DebugStop::Breakpoint(word));
+                }
+            }
+            first = false;
+            if let Err(e) = step(&mut self.program) {
+                let result = Err(e);
+                trace_finished(&mut self.program, &result);
+                return self.finish(result).map(DebugStop::Finished);
+            }
+        }
+    }
+
+    fn debug_end(self: &mut Self) -> Result<DebugStop, StaplError> {
+        let result = Ok(());
+        trace_finished(&mut self.program, &result);
+        let values = self.finish(result)?;
+        return Ok(DebugStop::Finished(values));
+    }
+
+    // the word about to be applied, if it has a breakpoint. a call stops once,
+    // when it has its arguments, not also when the word is looked up
+    fn breakpoint_hit(self: &Self) -> Option<String> {
+        let word = match self.debug_pending() {
+            Pending::Apply(w) => w,
+            _ => {
+                return None;
+            }
+        };
+        if self.breakpoints.contains(&word) {
+            return Some(word);
+        }
+        return None;
+    }
+
+    pub fn add_breakpoint(self: &mut Self, word: &str) -> () {
+        if !self.breakpoints.iter().any(|b| b == word) {
+            self.breakpoints.push(word.to_string());
+        }
+    }
+
+    pub fn remove_breakpoint(self: &mut Self, word: &str) -> bool {
+        let before = self.breakpoints.len();
+        self.breakpoints.retain(|b| b != word);
+        return self.breakpoints.len() < before;
+    }
+
+    pub fn breakpoints(self: &Self) -> &Vec<String> {
+        return &self.breakpoints;
+    }
+
+    // the istack, each pending call with what it still expects and what it has bound, and the ostack
+    pub fn debug_view(self: &Self) -> String {
+        let program = &self.program;
+        let mut view = format!("istack: {}\ncstack:", format_lexstack(&program.istack));
+        if program.cstack.stack.len() == 0 {
+            view = format!("{} None", view);
+        }
+        for (depth, call) in program.cstack.stack.iter().enumerate().rev() {
+            view = format!(
+                "{}\n  [{}] {} expects: {}| args: {}",
+                view,
+                depth + 1,
+                call.name,
+                format_expstack(&call.expectations),
+                format_parstack(&call.arguments)
+            );
+        }
+        let start = self.start.min(program.ostack.len());
+        view = format!(
+            "{}\nostack: {}",
+            view,
+            format_values(&program.ostack[start..].to_vec())
+        );
+        let next = match self.debug_pending() {
+            Pending::Apply(w) => format!("apply {}", w),
+            Pending::Push(item) => format!("push {}", format_source(&item)),
+            Pending::Done => "done".to_string(),
+        };
+        return format!("{}\nnext: {}", view, next);
+    }
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+
+use crate::format::print_lexeme;
+use crate::lex::{Expectation, LexItem};
+use crate::limits::Limit;
+use crate::span::Span;
+
+#[derive(Debug, Clone)]
+pub enum StaplError {
+    TypeMismatch {
+        word: String,
+        expected: Expectation,
+        found: LexItem,
+        span: Span,
+    },
+    UnknownWord {
+        word: String,
+        span: Span,
+    },
+    StackUnderflow {
+        word: String,
+        span: Span,
+    },
+    IndexOutOfRange {
+        word: String,
+        index: i64,
+        len: usize,
+        span: Span,
+    },
+    Arity {
+        word: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    // a word was asked for fewer than zero values
+    NegativeCount {
+        word: String,
+        count: i64,
+        span: Span,
+    },
+    KeyNotFound {
+        word: String,
+        key: LexItem,
+        span: Span,
+    },
+    Overflow {
+        word: String,
+        span: Span,
+    },
+    DivisionByZero {
+        word: String,
+        span: Span,
+    },
+    // the evaluation was stopped but left in place, see Interpreter::resume
+    LimitExceeded {
+        limit: Limit,
+        span: Span,
+    },
+    // source that could not be read, nothing of it is evaluated
+    Syntax {
+        message: String,
+        span: Span,
+    },
+}
+impl StaplError {
+    // where in the source the error should be reported
+    pub fn span(self: &Self) -> &Span {
+        match self {
+            StaplError::TypeMismatch { span, .. }
+            | StaplError::UnknownWord { span, .. }
+            | StaplError::StackUnderflow { span, .. }
+            | StaplError::IndexOutOfRange { span, .. }
+            | StaplError::Arity { span, .. }
+            | StaplError::NegativeCount { span, .. }
+            | StaplError::KeyNotFound { span, .. }
+            | StaplError::Overflow { span, .. }
+            | StaplError::DivisionByZero { span, .. }
+            | StaplError::LimitExceeded { span, .. }
+            | StaplError::Syntax { span, .. } => {
+                return span;
+            }
+        }
+    }
+}
+impl fmt::Display for StaplError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StaplError::TypeMismatch {
+                word,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "type mismatch in {}: expected {:?}, found {}",
+                word,
+                expected,
+                print_lexeme(found).trim_end()
+            ),
+            StaplError::UnknownWord { word, .. } => write!(f, "unknown word {}", word),
+            StaplError::StackUnderflow { word, .. } => {
+                write!(f, "stack underflow in {}: not enough arguments", word)
+            }
+            StaplError::IndexOutOfRange {
+                word, index, len, ..
+            } => write!(
+                f,
+                "index out of range in {}: {} is outside a stack of {}",
+                word, index, len
+            ),
+            StaplError::Arity {
+                word,
+                expected,
+                found,
+                ..
+            } => write!(
+                f,
+                "arity error in {}: expected {} arguments, found {}",
+                word, expected, found
+            ),
+            StaplError::NegativeCount { word, count, .. } => {
+                write!(f, "negative count in {}: {}", word, count)
+            }
+            StaplError::KeyNotFound { word, key, .. } => {
+                write!(
+                    f,
+                    "key not found in {}: {}",
+                    word,
+                    print_lexeme(key).trim_end()
+                )
+            }
+            StaplError::Overflow { word, .. } => {
+                write!(f, "overflow in {}: result does not fit in a number", word)
+            }
+            StaplError::DivisionByZero { word, .. } => write!(f, "division by zero in {}", word),
+            StaplError::LimitExceeded { limit, .. } => {
+                write!(f, "evaluation limit exceeded: {}", limit)
+            }
+            StaplError::Syntax { message, .. } => write!(f, "syntax error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for StaplError {}
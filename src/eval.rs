@@ -0,0 +1,739 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::builtins::{action_substitution, lambda_body};
+use crate::error::StaplError;
+use crate::format::{format_lexstack, format_parstack};
+use crate::lex::{parse_input, parse_stacks, Expectation, LexItem};
+use crate::limits::{Budget, EvalLimits, Limit};
+use crate::maps::check_keys;
+use crate::number::Number;
+use crate::span::{Source, Span};
+use crate::trace::{TraceEvent, TraceLevel, Tracer};
+
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct Parameter {
+    pub(crate) name: String,
+    pub(crate) value: LexItem,
+}
+
+// a native builtin, called once its call has all of its arguments
+pub type Action = Rc<dyn Fn(&mut Call, &mut Env) -> Result<(), StaplError>>;
+
+// the parts of the interpreter a builtin may use besides its own call.
+// caller is the call below it that is still collecting arguments, None at top level
+pub struct Env<'e> {
+    pub(crate) words: &'e mut HashMap<String, Word>,
+    pub(crate) ostack: &'e mut Vec<LexItem>,
+    pub(crate) caller: Option<&'e mut Call>,
+    pub(crate) limits: &'e EvalLimits,
+    pub(crate) budget: &'e Budget,
+    // how many items the istack and ostack held when the call was applied
+    pub(crate) items: usize,
+}
+impl<'e> Env<'e> {
+    pub fn output(self: &mut Self, value: LexItem) -> () {
+        self.ostack.push(value);
+    }
+    pub fn is_defined(self: &Self, name: &str) -> bool {
+        return self.words.contains_key(name);
+    }
+    // for builtins that build values as large as their arguments ask for:
+    // fails before adding that many items would go over the item limit
+    pub fn check_items(self: &Self, added: usize, span: &Span) -> Result<(), StaplError> {
+        let items = self.items.saturating_add(added);
+        if let Some(limit) = self.budget.check(self.limits, items) {
+            return Err(StaplError::LimitExceeded {
+                limit: limit,
+                span: span.clone(),
+            });
+        }
+        return Ok(());
+    }
+}
+
+pub struct Call {
+    pub(crate) name: String,
+    pub(crate) action: Action,
+    pub(crate) arity: usize,
+    pub(crate) arguments: Vec<Parameter>,
+    pub(crate) expectations: Vec<Expectation>,
+    // what each argument bound since the call was last applied was matched
+    // against, the newest last. they are the first arguments, the rest were
+    // kept or left over by an application that asked for more
+    pub(crate) matched: Vec<Expectation>,
+    pub(crate) results: Vec<LexItem>,
+    pub(crate) substitution: Option<LexItem>,
+    pub(crate) span: Span,
+    // set by a builtin that asked for more arguments, for its next application
+    pub(crate) ready: bool,
+}
+impl Call {
+    pub fn pop_argument(self: &mut Self) -> Result<LexItem, StaplError> {
+        match self.arguments.pop() {
+            Some(a) => {
+                return Ok(a.value);
+            }
+            None => {
+                return Err(StaplError::StackUnderflow {
+                    word: self.name.to_string(),
+                    span: self.span.clone(),
+                });
+            }
+        }
+    }
+    pub fn pop_num(self: &mut Self) -> Result<Number, StaplError> {
+        let value = self.pop_argument()?;
+        match value.get_Num() {
+            Some(n) => {
+                return Ok(n);
+            }
+            None => {
+                return Err(self.type_mismatch(Expectation::Num, value));
+            }
+        }
+    }
+    // a number small enough to count or index with
+    pub fn pop_int(self: &mut Self) -> Result<i64, StaplError> {
+        let value = self.pop_argument()?;
+        match value.get_Num().and_then(|n| n.to_i64()) {
+            Some(n) => {
+                return Ok(n);
+            }
+            None => {
+                return Err(self.type_mismatch(Expectation::Num, value));
+            }
+        }
+    }
+    pub fn pop_str(self: &mut Self) -> Result<String, StaplError> {
+        let value = self.pop_argument()?;
+        match value {
+            LexItem::Str(s, _) => {
+                return Ok(s);
+            }
+            _ => {
+                return Err(self.type_mismatch(Expectation::Str, value));
+            }
+        }
+    }
+    pub fn pop_stack(self: &mut Self) -> Result<Vec<LexItem>, StaplError> {
+        let value = self.pop_argument()?;
+        match value {
+            LexItem::Stack(s, _) => {
+                return Ok(s);
+            }
+            _ => {
+                return Err(self.type_mismatch(Expectation::Stack, value));
+            }
+        }
+    }
+    // points at the offending value when it came from source, otherwise at the call
+    pub fn type_mismatch(self: &Self, expected: Expectation, found: LexItem) -> StaplError {
+        let mut span = found.span().clone();
+        if !span.is_known() {
+            span = self.span.clone();
+        }
+        return StaplError::TypeMismatch {
+            word: self.name.to_string(),
+            expected: expected,
+            found: found,
+            span: span,
+        };
+    }
+    // the span of the word this call came from, for values the call produces
+    pub fn span(self: &Self) -> &Span {
+        return &self.span;
+    }
+    pub fn push_result(self: &mut Self, value: LexItem) -> () {
+        self.results.push(value);
+    }
+    // keeps a value for the next application of a call that asks for more
+    // arguments, kept values are popped before the ones that arrive
+    pub(crate) fn keep_argument(self: &mut Self, value: LexItem) -> () {
+        self.arguments.push(Parameter {
+            name: "kept".to_string(),
+            value: value,
+        });
+    }
+}
+impl fmt::Debug for Call {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}, args:{}, result:{}",
+            self.name,
+            format_parstack(&self.arguments),
+            format_lexstack(&self.results)
+        )
+    }
+}
+pub(crate) struct CallStack {
+    pub(crate) stack: Vec<Call>,
+    pub(crate) words: HashMap<String, Word>,
+    pub(crate) tracer: Tracer,
+}
+impl CallStack {
+    fn trace_pushed(self: &mut Self) -> () {
+        let depth = self.stack.len();
+        if let Some(call) = self.stack.last() {
+            self.tracer
+                .emit(TraceLevel::Steps, || TraceEvent::CallPushed {
+                    word: call.name.to_string(),
+                    depth: depth,
+                    expectations: call.expectations.clone(),
+                    span: call.span.clone(),
+                });
+        }
+    }
+    pub(crate) fn pushSearchWord(
+        self: &mut Self,
+        word: String,
+        span: Span,
+    ) -> Result<(), StaplError> {
+        match self.words.get(&word) {
+            Some(w) => {
+                self.stack.push(make_call(w, span));
+                self.trace_pushed();
+            }
+            None => {
+                let defword = match self.words.get(&"define".to_string()) {
+                    Some(d) => d,
+                    None => {
+                        return Err(StaplError::UnknownWord {
+                            word: word,
+                            span: span,
+                        });
+                    }
+                };
+
+                let mut c = make_call(defword, span.clone());
+
+                self.stack.push(c);
+                self.trace_pushed();
+
+                self.pushLexItem(&mut LexItem::Word(word, span))?;
+            }
+        }
+        return Ok(());
+    }
+    pub(crate) fn pushWordCall(self: &mut Self, word: &mut Word, span: Span) -> () {
+        //println!("pushWordCall {:?}", word);
+        self.stack.push(make_call(word, span));
+        self.trace_pushed();
+
+        return ();
+    }
+
+    pub(crate) fn len(self: &Self) -> usize {
+        return self.stack.len();
+    }
+
+    pub(crate) fn getExpectation(self: &Self) -> Option<&Expectation> {
+        let top_call: &Call;
+        match self.stack.last() {
+            Some(call) => {
+                top_call = call;
+            }
+            None => {
+                return None;
+            }
+        };
+
+        return top_call.expectations.last();
+    }
+
+    pub(crate) fn pushLexItem<'l>(
+        self: &mut Self,
+        lexeme: &'l mut LexItem,
+    ) -> Result<Option<&'l mut LexItem>, StaplError> {
+        if let Some(top_call) = self.stack.last_mut() {
+            let e = &mut top_call.expectations;
+            match e.last() {
+                Some(&top_expectation) => {
+                    //let d = check_expectation(top_expectation, lexeme.clone());
+
+                    //let dataitem = lexeme;
+                    let mut expectation_match = true;
+                    match (&top_expectation, &lexeme) {
+                        (Expectation::Num, LexItem::Num(..))
+                        | (Expectation::NumStaLit, LexItem::Num(..)) => (),
+                        (Expectation::Word, LexItem::Word(..)) => (),
+                        (Expectation::Parameter, LexItem::Parameter(..)) => (),
+                        (Expectation::Stack, LexItem::Stack(..))
+                        | (Expectation::NumStaLit, LexItem::Stack(..)) => (),
+                        (Expectation::Stack, LexItem::Literal(..))
+                        | (Expectation::NumStaLit, LexItem::Literal(..)) => (),
+                        (Expectation::Lambda, LexItem::Lambda(..))
+                        | (Expectation::Lambda, LexItem::Closure(..)) => (),
+                        (Expectation::Str, LexItem::Str(..))
+                        | (Expectation::NumStaLit, LexItem::Str(..)) => (),
+                        (Expectation::Map, LexItem::Map(..))
+                        | (Expectation::NumStaLit, LexItem::Map(..)) => (),
+                        (Expectation::NumStaLit, LexItem::Bool(..))
+                        | (Expectation::NumStaLit, LexItem::Nil(..)) => (),
+                        (Expectation::Any, _) => (),
+                        (Expectation::Value, LexItem::Num(..))
+                        | (Expectation::Value, LexItem::Stack(..))
+                        | (Expectation::Value, LexItem::Literal(..))
+                        | (Expectation::Value, LexItem::Str(..))
+                        | (Expectation::Value, LexItem::Map(..))
+                        | (Expectation::Value, LexItem::Bool(..))
+                        | (Expectation::Value, LexItem::Nil(..))
+                        | (Expectation::Value, LexItem::Lambda(..))
+                        | (Expectation::Value, LexItem::Closure(..)) => (),
+                        (_, LexItem::Num(..))
+                        | (_, LexItem::Stack(..))
+                        | (_, LexItem::Literal(..))
+                        | (_, LexItem::Str(..))
+                        | (_, LexItem::Map(..))
+                        | (_, LexItem::Bool(..))
+                        | (_, LexItem::Nil(..)) => {
+                            return Err(top_call.type_mismatch(top_expectation, lexeme.clone()));
+                        }
+                        _ => {
+                            expectation_match = false;
+                        }
+                    };
+
+                    if expectation_match {
+                        e.pop();
+                        top_call.matched.push(top_expectation);
+                        let parname = format!("{}", top_call.arguments.len() + 1);
+                        top_call.arguments.insert(
+                            0,
+                            Parameter {
+                                name: parname.to_string(),
+                                value: lexeme.to_owned(),
+                            },
+                        );
+                        self.tracer
+                            .emit(TraceLevel::Full, || TraceEvent::ArgumentBound {
+                                word: top_call.name.to_string(),
+                                name: parname,
+                                value: lexeme.clone(),
+                            });
+                        return Ok(None);
+                    }
+                    //could put apply here
+                }
+                None => {}
+            }
+        }
+        /* println!(
+            "pushed item: {}, Stack: {:?}",
+            print_lexeme(top_call.arguments.last().unwrap()),
+            self.stack
+        ); */
+        return Ok(Some(lexeme));
+    }
+
+    pub(crate) fn wantsData(self: &Self) -> bool {
+        match self.stack.last() {
+            Some(call) => {
+                return call.expectations.len() > 0;
+            }
+            None => {
+                return false;
+            }
+        };
+    }
+    pub(crate) fn top_apply(
+        self: &mut Self,
+        result: &mut Vec<LexItem>,
+        ostack: &mut Vec<LexItem>,
+        limits: &EvalLimits,
+        budget: &Budget,
+    ) -> Result<bool, StaplError> {
+        match self.stack.split_last_mut() {
+            Some((top_call, below)) => {
+                let substituted = top_call.substitution.is_some();
+                let action = top_call.action.clone();
+                let items = result.len() + ostack.len();
+                let mut env = Env {
+                    words: &mut self.words,
+                    ostack: ostack,
+                    caller: below.last_mut(),
+                    limits: limits,
+                    budget: budget,
+                    items: items,
+                };
+                action(top_call, &mut env)?;
+                if top_call.expectations.len() > 0 {
+                    top_call.matched.clear();
+                    // the action asked for more arguments, as take does once it has its count.
+                    // whatever it returned is evaluated first and can supply them, which is
+                    // how map gets the value of its lambda for each element
+                    result.append(&mut top_call.results);
+                    return Ok(true);
+                }
+
+                let results = &top_call.results;
+                if substituted {
+                    self.tracer
+                        .emit(TraceLevel::Steps, || TraceEvent::Substituted {
+                            word: top_call.name.to_string(),
+                            body: results.clone(),
+                        });
+                } else {
+                    self.tracer.emit(TraceLevel::Steps, || TraceEvent::Applied {
+                        word: top_call.name.to_string(),
+                        results: results.clone(),
+                    });
+                }
+
+                // the call is gone before its results are evaluated, so a word called
+                // in tail position of a substituted body runs in its caller's place
+                result.append(&mut top_call.results);
+                //result.insert(0, LexItem::Stack(top_call.results.clone()));
+
+                self.stack.pop();
+                return Ok(true);
+            }
+            _ => {
+                return Ok(false);
+            }
+        }
+    }
+}
+
+//#[derive(Debug)]
+#[derive(Clone)]
+pub(crate) struct Word {
+    pub(crate) name: String,
+    pub(crate) arity: usize,
+    pub(crate) action: Action,
+    pub(crate) substitution: Option<LexItem>,
+    pub(crate) expectations: Vec<Expectation>,
+}
+impl Word {
+    pub(crate) fn make_word<'w>(
+        name: String,
+        arity: usize,
+        action: Action,
+        //expectations: &'w mut Vec<Expectation>,
+        substitution: Option<LexItem>,
+    ) -> Word {
+        let mut expectations: Vec<Expectation> = Vec::new();
+        let sub = substitution.clone();
+        match substitution {
+            Some(s) => {
+                expectations = s.get_expectations();
+            }
+            None => (),
+        }
+        let mut word = Word {
+            name: name,
+            arity: expectations.len(),
+            expectations: expectations,
+            action: action,
+            substitution: sub,
+        };
+
+        return word;
+    }
+    pub(crate) fn setup_expectations(self: &mut Self) -> &mut Self {
+        let sub = &mut self.substitution;
+        let mut e = &mut self.expectations;
+        match sub {
+            Some(s) => {
+                let e2 = s.get_expectations();
+                self.expectations = e2;
+            }
+            None => {}
+        }
+        return self;
+    }
+}
+
+pub(crate) fn make_call(word: &Word, span: Span) -> Call {
+    let sub = word.substitution.clone();
+    let a = Call {
+        name: word.name.to_string(),
+        action: word.action.clone(),
+        arity: word.arity,
+        arguments: Vec::new(),
+        expectations: word.expectations.to_vec(),
+        matched: Vec::new(),
+        results: Vec::new(),
+        substitution: sub,
+        span: span,
+        ready: false,
+    };
+
+    return a;
+}
+
+pub(crate) struct Program {
+    pub(crate) name: String,
+    pub(crate) istack: Vec<LexItem>,
+    pub(crate) cstack: CallStack,
+    pub(crate) ostack: Vec<LexItem>,
+    pub(crate) limits: EvalLimits,
+    pub(crate) budget: Budget,
+}
+
+// counts a step, stopping before it when a limit is reached so that the
+// istack, cstack and ostack are left as they were. only steps and time start
+// over when eval picks up again, the call depth and stack items would still be
+// over their limits, so those stops are not marked as resumable. a builtin that
+// runs into a limit has already taken its arguments and can't be resumed either
+fn check_limits(
+    budget: &mut Budget,
+    limits: &EvalLimits,
+    istack: &Vec<LexItem>,
+    cstack: &CallStack,
+    ostack: &Vec<LexItem>,
+) -> Result<(), StaplError> {
+    let items = istack.len() + ostack.len();
+    if let Some(limit) = budget.step(limits, cstack.len(), items) {
+        budget.stopped = match limit {
+            Limit::Steps(_) | Limit::Timeout(_) => true,
+            Limit::CallDepth(_) | Limit::StackItems(_) => false,
+        };
+        let span = match (istack.last(), cstack.stack.last()) {
+            (Some(item), _) => item.span().clone(),
+            (None, Some(call)) => call.span.clone(),
+            (None, None) => Span::default(),
+        };
+        return Err(StaplError::LimitExceeded {
+            limit: limit,
+            span: span,
+        });
+    }
+    return Ok(());
+}
+
+// what eval will do on its next step
+#[derive(Debug, Clone)]
+pub enum Pending {
+    Apply(String),
+    Push(LexItem),
+    Done,
+}
+
+pub(crate) fn pending(program: &Program) -> Pending {
+    let cstack = &program.cstack;
+    if !cstack.wantsData() {
+        if let Some(call) = cstack.stack.last() {
+            return Pending::Apply(call.name.to_string());
+        }
+    }
+    match program.istack.last() {
+        Some(item) => {
+            return Pending::Push(item.clone());
+        }
+        None => {
+            return Pending::Done;
+        }
+    }
+}
+
+pub(crate) fn eval<'o>(program: &'o mut Program) -> Result<&'o mut Program, StaplError> {
+    while step(program)? {}
+    return Ok(program);
+}
+
+// applies the innermost call if it has all of its arguments, otherwise takes
+// the next item from the istack. returns false once there is nothing left to do
+pub(crate) fn step(program: &mut Program) -> Result<bool, StaplError> {
+    let mut istack = &mut program.istack;
+    let mut cstack = &mut program.cstack;
+    let mut ostack = &mut program.ostack;
+    let limits = &program.limits;
+    let budget = &mut program.budget;
+
+    if !cstack.wantsData() && cstack.len() > 0 {
+        check_limits(budget, limits, istack, cstack, ostack)?;
+        cstack.top_apply(&mut istack, &mut ostack, limits, budget)?;
+        return Ok(true);
+    }
+    if istack.len() == 0 {
+        return Ok(false);
+    }
+    check_limits(budget, limits, istack, cstack, ostack)?;
+    match istack.pop().unwrap() {
+        LexItem::Word(w, span) => {
+            let mut pw = &mut LexItem::Word(w, span);
+            let lexreturn = cstack.pushLexItem(&mut pw)?;
+            match lexreturn {
+                Some(LexItem::Word(w, span)) => {
+                    cstack.pushSearchWord(w.to_string(), span.clone())?;
+                }
+                _ => {}
+            }
+        }
+
+        LexItem::Num(n, span) => {
+            let mut num = LexItem::Num(n, span);
+            if cstack.pushLexItem(&mut num)?.is_some() {
+                output(cstack, ostack, num);
+            }
+        }
+        mut lambda @ LexItem::Lambda(..) | mut lambda @ LexItem::Closure(..) => {
+            let lexreturn = cstack.pushLexItem(&mut lambda)?;
+            match lexreturn {
+                Some(l) if l.get_expectations().len() == 0 => {
+                    // nothing to bind, so the body runs in place of the lambda without a call
+                    let mut body = lambda_body(l.clone(), &Vec::new());
+                    cstack
+                        .tracer
+                        .emit(TraceLevel::Steps, || TraceEvent::Substituted {
+                            word: "lambda".to_string(),
+                            body: body.clone(),
+                        });
+                    istack.extend(body.drain(..));
+                }
+                Some(l) => {
+                    let arity = l.get_arity();
+                    let mut w = Word::make_word(
+                        "lambda".to_string(),
+                        arity,
+                        Rc::new(action_substitution),
+                        Some(l.clone()),
+                    );
+                    w.setup_expectations();
+                    cstack.pushWordCall(&mut w, l.span().clone());
+                    //istack.append(l);
+                }
+                _ => {}
+            }
+        }
+        LexItem::Stack(mut s, span) => {
+            if let Some(l) = cstack.pushLexItem(&mut LexItem::Stack(s, span))? {
+                output(cstack, ostack, l.clone());
+            }
+        }
+        LexItem::Literal(l, span) => {
+            if let Some(l) = cstack.pushLexItem(&mut LexItem::Literal(l, span))? {
+                output(cstack, ostack, l.clone());
+            }
+        }
+        LexItem::Str(l, span) => {
+            if let Some(l) = cstack.pushLexItem(&mut LexItem::Str(l, span))? {
+                output(cstack, ostack, l.clone());
+            }
+        }
+        mut value @ LexItem::Bool(..) | mut value @ LexItem::Nil(..) => {
+            if let Some(l) = cstack.pushLexItem(&mut value)? {
+                output(cstack, ostack, l.clone());
+            }
+        }
+        LexItem::Map(m, span) => {
+            if let Some(key) = check_keys(&m) {
+                return Err(StaplError::TypeMismatch {
+                    word: "map".to_string(),
+                    expected: Expectation::Literal,
+                    found: key,
+                    span: span,
+                });
+            }
+            if let Some(l) = cstack.pushLexItem(&mut LexItem::Map(m, span))? {
+                output(cstack, ostack, l.clone());
+            }
+        }
+        LexItem::Parameter(p, span) => {
+            // only a call asking for a name, like let, can take a parameter that
+            // was not replaced by beta reduction
+            let mut parameter = LexItem::Parameter(p, span);
+            if let Some(LexItem::Parameter(p, span)) = cstack.pushLexItem(&mut parameter)? {
+                return Err(StaplError::UnknownWord {
+                    word: format!("${}", p),
+                    span: span.clone(),
+                });
+            }
+        }
+        _ => (),
+    }
+    return Ok(true);
+}
+
+// a value that no call is waiting for
+fn output(cstack: &mut CallStack, ostack: &mut Vec<LexItem>, value: LexItem) -> () {
+    cstack.tracer.emit(TraceLevel::Full, || TraceEvent::Output {
+        value: value.clone(),
+    });
+    ostack.push(value);
+}
+
+pub(crate) fn trace_finished(program: &mut Program, result: &Result<(), StaplError>) -> () {
+    let budget = &program.budget;
+    let outputs = program.ostack.len();
+    program
+        .cstack
+        .tracer
+        .emit(TraceLevel::Summary, || TraceEvent::Finished {
+            steps: budget.steps,
+            elapsed: budget.started.elapsed(),
+            outputs: outputs,
+            error: result.as_ref().err().map(|e| e.to_string()),
+        });
+}
+
+pub(crate) fn new_program(name: &str) -> Program {
+    let mut cstack = CallStack {
+        stack: Vec::new(),
+        words: HashMap::new(),
+        tracer: Tracer::new(),
+    };
+    cstack.create_builtin_words();
+
+    let program = Program {
+        name: name.to_string(),
+        cstack: cstack,
+        istack: Vec::new(),
+        ostack: Vec::new(),
+        limits: EvalLimits::default(),
+        budget: Budget::new(),
+    };
+    return program;
+}
+
+// lexes and parses source onto the istack without evaluating it
+pub(crate) fn load_source(program: &mut Program, source: String) -> Result<(), StaplError> {
+    let mut lexstack = &mut Vec::new();
+    let source = Rc::new(Source::new(&program.name, &source));
+    parse_input(&source, &mut lexstack)?;
+    program.istack = parse_stacks(lexstack, &mut program.istack, false)?.to_owned();
+    program.budget = Budget::new();
+    return Ok(());
+}
+
+pub(crate) fn run_source(program: &mut Program, source: String) -> Result<(), StaplError> {
+    load_source(program, source)?;
+    let result = eval(program).map(|_| ());
+    trace_finished(program, &result);
+    return result;
+}
+
+// carries on with an evaluation that stopped at a limit, with a fresh budget
+pub(crate) fn resume(program: &mut Program) -> Result<(), StaplError> {
+    program.budget = Budget::new();
+    let result = eval(program).map(|_| ());
+    trace_finished(program, &result);
+    return result;
+}
+
+pub(crate) fn check_complete(program: &Program) -> Result<(), StaplError> {
+    if let Some(call) = program.cstack.stack.last() {
+        if call.name == "define" {
+            if let Some(Parameter {
+                value: LexItem::Word(w, span),
+                ..
+            }) = call.arguments.last()
+            {
+                return Err(StaplError::UnknownWord {
+                    word: w.to_string(),
+                    span: span.clone(),
+                });
+            }
+        }
+        return Err(StaplError::Arity {
+            word: call.name.to_string(),
+            expected: call.arguments.len() + call.expectations.len(),
+            found: call.arguments.len(),
+            span: call.span.clone(),
+        });
+    }
+    return Ok(());
+}
@@ -0,0 +1,162 @@
+use crate::builtins::lambda_body;
+use crate::error::StaplError;
+use crate::eval::Parameter;
+use crate::lex::{Expectation, LexItem};
+use crate::maps::map_items;
+
+// a stack stored last item first, like the istack, a body or the results of a
+// call, written in the order it is read
+pub fn format_lexstack(stack: &Vec<LexItem>) -> String {
+    let written: Vec<String> = stack.iter().rev().map(format_source).collect();
+    return written.join(" ");
+}
+pub(crate) fn format_expstack(stack: &Vec<Expectation>) -> String {
+    let mut result = "".to_string();
+    let stack_iter = stack.iter().rev();
+    for i in stack_iter {
+        result = format!("{}{:?} ", result, i);
+    }
+    return result;
+}
+
+// the arguments of a call, the first one bound first
+pub(crate) fn format_parstack(stack: &Vec<Parameter>) -> String {
+    let written: Vec<String> = stack
+        .iter()
+        .rev()
+        .map(|p| format!("(${}) {}", p.name, format_source(&p.value)))
+        .collect();
+    return written.join(" ");
+}
+
+// the canonical form of a value followed by a space
+pub fn print_lexeme(token: &LexItem) -> String {
+    return format!("{} ", format_source(token));
+}
+
+// the canonical form of a value: source that parses back to an equal value.
+// closures are written as the lambda they stand for, and floats that are not
+// finite can't be read back
+pub fn format_source(token: &LexItem) -> String {
+    match token {
+        LexItem::Word(w, _) => {
+            return w.to_string();
+        }
+        LexItem::Num(n, _) => {
+            return n.to_string();
+        }
+        LexItem::Literal(l, _) => {
+            return format!("'{}", l);
+        }
+        LexItem::Str(s, _) => {
+            return format!("\"{}\"", escape_string(s));
+        }
+        LexItem::Parameter(p, _) => {
+            return format!("${}", p);
+        }
+        LexItem::Bool(b, _) => {
+            return b.to_string();
+        }
+        LexItem::Nil(_) => {
+            return "nil".to_string();
+        }
+        LexItem::Stack(s, _) => {
+            return format!("'[ {}]", format_items(s));
+        }
+        LexItem::Lambda(s, _) => {
+            return format!("[ {}]", format_items(s));
+        }
+        LexItem::Closure(..) => {
+            return format!(
+                "[ {}]",
+                format_items(&lambda_body(token.clone(), &Vec::new()))
+            );
+        }
+        LexItem::Map(m, _) => {
+            return format!("{{ {}}}", format_items(&map_items(m)));
+        }
+        LexItem::OpenParen(_) => {
+            return "[".to_string();
+        }
+        LexItem::CloseParen(_) => {
+            return "]".to_string();
+        }
+        LexItem::OpenBrace(_) => {
+            return "{".to_string();
+        }
+        LexItem::CloseBrace(_) => {
+            return "}".to_string();
+        }
+        LexItem::Quote(_) => {
+            return "'".to_string();
+        }
+    }
+}
+
+// values in the order they are given, like the output stack oldest first
+pub fn format_values(values: &Vec<LexItem>) -> String {
+    let written: Vec<String> = values.iter().map(format_source).collect();
+    return written.join(" ");
+}
+
+// items stored last first, written first first
+fn format_items(items: &Vec<LexItem>) -> String {
+    let mut result = "".to_string();
+    for i in items.iter().rev() {
+        result = format!("{}{} ", result, format_source(i));
+    }
+    return result;
+}
+
+fn escape_string(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\0' => escaped.push_str("\\0"),
+            _ => escaped.push(c),
+        }
+    }
+    return escaped;
+}
+
+// the error message followed by the line of source it points at, with carets under its span
+pub fn format_diagnostic(error: &StaplError) -> String {
+    let span = error.span();
+    let source = match &span.source {
+        Some(s) => s,
+        None => {
+            return format!("error: {}", error);
+        }
+    };
+    let line = source.line(span.line);
+    let gutter = span.line.to_string();
+    let pad = " ".repeat(gutter.len());
+    let indent: String = line
+        .chars()
+        .take(span.column - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    let mut width = source.text[span.start..span.end]
+        .chars()
+        .take_while(|c| *c != '\n')
+        .count();
+    if width == 0 {
+        width = 1;
+    }
+    return format!(
+        "{}: error: {}\n{} |\n{} | {}\n{} | {}{}",
+        span,
+        error,
+        pad,
+        gutter,
+        line,
+        pad,
+        indent,
+        "^".repeat(width)
+    );
+}
@@ -0,0 +1,171 @@
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Helper};
+use std::borrow::Cow::{self, Borrowed, Owned};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+
+use third::{is_balanced, lex, Expectation, Interpreter, LexItem};
+
+pub const COMMANDS: [&str; 11] = [
+    ":words",
+    ":see",
+    ":forget",
+    ":reset",
+    ":load",
+    ":save",
+    ":save-image",
+    ":load-image",
+    ":trace",
+    ":debug",
+    ":help",
+];
+
+// what the words of the dictionary expect, first argument first
+pub fn describe(expectations: &Vec<Expectation>) -> String {
+    let kinds: Vec<String> = expectations.iter().map(|e| format!("{:?}", e)).collect();
+    return kinds.join(" ");
+}
+
+// completes, highlights and hints from a copy of the dictionary, which the
+// REPL refreshes after every line
+pub struct ReplHelper {
+    words: BTreeMap<String, Vec<Expectation>>,
+    // whether the input being edited has been continued on a new line
+    continued: Cell<bool>,
+}
+
+impl ReplHelper {
+    pub fn new(interpreter: &Interpreter) -> ReplHelper {
+        let mut helper = ReplHelper {
+            words: BTreeMap::new(),
+            continued: Cell::new(false),
+        };
+        helper.refresh(interpreter);
+        return helper;
+    }
+
+    pub fn refresh(self: &mut Self, interpreter: &Interpreter) -> () {
+        self.words.clear();
+        for name in interpreter.words() {
+            let expectations = interpreter.word_expectations(&name).unwrap_or_default();
+            self.words.insert(name, expectations);
+        }
+    }
+
+    // whether the input that was just given up on ran over several lines,
+    // forgetting it for the next input
+    pub fn take_continued(self: &Self) -> bool {
+        return self.continued.replace(false);
+    }
+}
+
+// where the word ending at pos starts, words stop at brackets and quotes
+fn word_start(line: &str, pos: usize) -> usize {
+    match line[..pos].rfind(|c: char| c.is_whitespace() || "[](){}'\"".contains(c)) {
+        Some(i) => {
+            return i + 1;
+        }
+        None => {
+            return 0;
+        }
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let start = word_start(line, pos);
+        let prefix = &line[start..pos];
+        if line[..start].trim().len() == 0 && prefix.starts_with(':') {
+            let commands = COMMANDS.iter().filter(|c| c.starts_with(prefix));
+            return Ok((start, commands.map(|c| c.to_string()).collect()));
+        }
+        let words = self.words.keys().filter(|w| w.starts_with(prefix));
+        return Ok((start, words.cloned().collect()));
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+
+    // what the word just typed expects, once the cursor is past its end
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context) -> Option<String> {
+        if pos < line.len() || line.trim_start().starts_with(':') {
+            return None;
+        }
+        let word = &line[word_start(line, pos)..pos];
+        let expectations = self.words.get(word)?;
+        if expectations.len() == 0 {
+            return None;
+        }
+        return Some(format!(" ( {} )", describe(expectations)));
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if line.trim_start().starts_with(':') {
+            return Borrowed(line);
+        }
+        let mut highlighted = String::new();
+        let mut end = 0;
+        for lexeme in lex(line) {
+            let span = lexeme.span();
+            let colour = match &lexeme {
+                LexItem::Num(..) | LexItem::Bool(..) | LexItem::Nil(..) => "33",
+                LexItem::Str(..) => "32",
+                LexItem::Parameter(..) => "36",
+                LexItem::Word(w, _) if self.words.contains_key(w) => "34",
+                LexItem::Word(..) => "1",
+                LexItem::Quote(..) => "35",
+                LexItem::OpenParen(..) | LexItem::CloseParen(..) => "35",
+                LexItem::OpenBrace(..) | LexItem::CloseBrace(..) => "35",
+                _ => {
+                    continue;
+                }
+            };
+            highlighted.push_str(&line[end..span.start]);
+            highlighted.push_str(&format!(
+                "\x1b[{}m{}\x1b[0m",
+                colour,
+                &line[span.start..span.end]
+            ));
+            end = span.end;
+        }
+        highlighted.push_str(&line[end..]);
+        return Owned(highlighted);
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        return Owned(format!("\x1b[90m{}\x1b[0m", hint));
+    }
+
+    fn highlight_char(&self, line: &str, _pos: usize) -> bool {
+        return line.len() > 0;
+    }
+}
+
+// enter starts a new line while a bracket or string is left open, commands are
+// always a single line. rustyline draws the lines after the first without a prompt
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+        if input.trim_start().starts_with(':') || is_balanced(input) {
+            self.continued.set(false);
+            return Ok(ValidationResult::Valid(None));
+        }
+        self.continued.set(true);
+        return Ok(ValidationResult::Incomplete);
+    }
+}
+
+impl Helper for ReplHelper {}
@@ -0,0 +1,106 @@
+use std::rc::Rc;
+
+use crate::builtins::action_substitution;
+use crate::eval::Word;
+use crate::format::format_source;
+use crate::interpreter::{Interpreter, Value};
+use crate::lex::{parse, Expectation};
+
+const HEADER: &str = "stapl-image 1";
+
+// an image keeps the defined words one to a line, as the name, what it
+// expects first argument first, and what it stands for written as source:
+//   fact Value = [ if = $n 0 [ 1 ] [ * $n fact - $n 1 ] ]
+impl Interpreter {
+    pub fn image(self: &Self) -> String {
+        let mut image = format!("{}\n", HEADER);
+        for name in self.words() {
+            let value = match self.definition(&name) {
+                Some(v) => v,
+                None => {
+                    continue;
+                }
+            };
+            let mut line = name.to_string();
+            for expectation in self.word_expectations(&name).unwrap_or_default() {
+                line = format!("{} {:?}", line, expectation);
+            }
+            image = format!("{}{} = {}\n", image, line, format_source(value));
+        }
+        return image;
+    }
+
+    // adds the words of an image to the dictionary, replacing any with the
+    // same name, and returns how many there were. nothing is added if any
+    // line is malformed
+    pub fn load_image(self: &mut Self, image: &str) -> Result<usize, String> {
+        let mut lines = image.lines().enumerate();
+        match lines.next() {
+            Some((_, header)) if header.trim() == HEADER => {}
+            _ => {
+                return Err(format!(
+                    "not an image, expected {} on the first line",
+                    HEADER
+                ));
+            }
+        }
+        let mut words = Vec::new();
+        for (number, line) in lines {
+            if line.trim().len() == 0 {
+                continue;
+            }
+            let word = image_word(line).map_err(|e| format!("line {}: {}", number + 1, e))?;
+            words.push(word);
+        }
+        let count = words.len();
+        for word in words {
+            self.program
+                .cstack
+                .words
+                .insert(word.name.to_string(), word);
+        }
+        return Ok(count);
+    }
+}
+
+fn image_word(line: &str) -> Result<Word, String> {
+    let (head, source) = match line.find(" = ") {
+        Some(i) => (&line[..i], &line[i + 3..]),
+        None => {
+            return Err("expected name, expectations, = and a value".to_string());
+        }
+    };
+    let mut head = head.split_whitespace();
+    let name = match head.next() {
+        Some(n) => n,
+        None => {
+            return Err("missing word name".to_string());
+        }
+    };
+    let mut expectations = Vec::new();
+    for kind in head {
+        expectations.insert(0, kind.parse::<Expectation>()?);
+    }
+    if source.trim().len() == 0 {
+        return Err("missing value".to_string());
+    }
+    let value = image_value(source).ok_or(format!("{} is not a single value", source.trim()))?;
+    let mut word = Word::make_word(
+        name.to_string(),
+        0,
+        Rc::new(action_substitution),
+        Some(value),
+    );
+    // the expectations saved win over the ones worked out from the value
+    word.arity = expectations.len();
+    word.expectations = expectations;
+    return Ok(word);
+}
+
+fn image_value(source: &str) -> Option<Value> {
+    let mut parsed = parse(source).ok()?;
+    if parsed.len() != 1 {
+        return None;
+    }
+    return parsed.pop();
+}
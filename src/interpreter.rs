@@ -0,0 +1,223 @@
+use std::rc::Rc;
+
+use crate::error::StaplError;
+use crate::eval::{check_complete, new_program, resume, run_source, Call, Env, Program};
+use crate::format::format_source;
+use crate::lex::{Expectation, LexItem};
+use crate::limits::EvalLimits;
+use crate::trace::{TraceEvent, TraceFormat, TraceLevel};
+
+pub type Value = LexItem;
+
+// an embeddable STAPL session: the dictionary persists between eval_str calls
+pub struct Interpreter {
+    pub(crate) program: Program,
+    pub(crate) backtrace: Vec<String>,
+    pub(crate) start: usize,
+    pub(crate) suspended: bool,
+    pub(crate) breakpoints: Vec<String>,
+}
+
+impl Interpreter {
+    pub fn new(name: &str) -> Interpreter {
+        let interpreter = Interpreter {
+            program: new_program(name),
+            backtrace: Vec::new(),
+            start: 0,
+            suspended: false,
+            breakpoints: Vec::new(),
+        };
+        return interpreter;
+    }
+
+    pub fn name(self: &Self) -> &str {
+        return &self.program.name;
+    }
+
+    // what spans in the source evaluated next are reported against
+    pub fn set_name(self: &mut Self, name: &str) -> () {
+        self.program.name = name.to_string();
+    }
+
+    // evaluates a complete expression and returns the values it output,
+    // on error the pending calls are discarded and the dictionary is kept.
+    // an evaluation that stopped at a limit is suspended instead, and is
+    // abandoned if another expression is evaluated before it is resumed
+    pub fn eval_str(self: &mut Self, source: &str) -> Result<Vec<Value>, StaplError> {
+        if self.suspended {
+            self.abort();
+        }
+        self.start = self.program.ostack.len();
+        let result = run_source(&mut self.program, source.to_string());
+        return self.finish(result);
+    }
+
+    // continues a suspended evaluation with a fresh budget
+    pub fn resume(self: &mut Self) -> Result<Vec<Value>, StaplError> {
+        if !self.suspended {
+            return Ok(Vec::new());
+        }
+        let result = resume(&mut self.program);
+        return self.finish(result);
+    }
+
+    // drops a suspended evaluation along with anything it had output
+    pub fn abort(self: &mut Self) -> () {
+        self.suspended = false;
+        self.program.ostack.truncate(self.start);
+        self.reset();
+    }
+
+    pub fn is_suspended(self: &Self) -> bool {
+        return self.suspended;
+    }
+
+    pub(crate) fn finish(
+        self: &mut Self,
+        result: Result<(), StaplError>,
+    ) -> Result<Vec<Value>, StaplError> {
+        let result = result.and_then(|_| check_complete(&self.program));
+        self.suspended = false;
+        // drop and clear at top level can take values output before this evaluation
+        if self.start > self.program.ostack.len() {
+            self.start = self.program.ostack.len();
+        }
+        match result {
+            Ok(_) => {
+                self.backtrace.clear();
+                return Ok(self.program.ostack[self.start..].to_vec());
+            }
+            Err(e) => {
+                self.backtrace = self
+                    .program
+                    .cstack
+                    .stack
+                    .iter()
+                    .rev()
+                    .map(|call| call.name.to_string())
+                    .collect();
+                if self.program.budget.stopped {
+                    self.suspended = true;
+                } else {
+                    self.reset();
+                }
+                return Err(e);
+            }
+        }
+    }
+
+    pub fn limits(self: &Self) -> &EvalLimits {
+        return &self.program.limits;
+    }
+
+    pub fn set_limits(self: &mut Self, limits: EvalLimits) -> () {
+        self.program.limits = limits;
+    }
+
+    pub fn trace_level(self: &Self) -> TraceLevel {
+        return self.program.cstack.tracer.level;
+    }
+
+    pub fn set_trace_level(self: &mut Self, level: TraceLevel) -> () {
+        self.program.cstack.tracer.level = level;
+    }
+
+    // how events are written to stderr when no sink is set
+    pub fn set_trace_format(self: &mut Self, format: TraceFormat) -> () {
+        self.program.cstack.tracer.format = format;
+    }
+
+    // hands every traced event to the host instead of printing it
+    pub fn set_trace_sink<F>(self: &mut Self, sink: F) -> ()
+    where
+        F: FnMut(&TraceEvent) + 'static,
+    {
+        self.program.cstack.tracer.sink = Some(Box::new(sink));
+    }
+
+    pub fn clear_trace_sink(self: &mut Self) -> () {
+        self.program.cstack.tracer.sink = None;
+    }
+
+    // expectations are listed first argument first, as word_expectations returns them
+    pub fn register_word<F>(
+        self: &mut Self,
+        name: &str,
+        expectations: Vec<Expectation>,
+        action: F,
+    ) -> ()
+    where
+        F: Fn(&mut Call, &mut Env) -> Result<(), StaplError> + 'static,
+    {
+        // the dictionary keeps them as a stack, the last entry is matched first
+        let expectations = expectations.into_iter().rev().collect();
+        self.program
+            .cstack
+            .add_builtin(name, expectations, Rc::new(action));
+    }
+
+    pub fn output(self: &Self) -> &Vec<Value> {
+        return &self.program.ostack;
+    }
+
+    pub fn clear_output(self: &mut Self) -> () {
+        self.program.ostack.clear();
+    }
+
+    // names of the calls that were pending when the last error occurred, innermost first
+    pub fn backtrace(self: &Self) -> &Vec<String> {
+        return &self.backtrace;
+    }
+
+    // every word in the dictionary, in name order
+    pub fn words(self: &Self) -> Vec<String> {
+        let mut names: Vec<String> = self.program.cstack.words.keys().cloned().collect();
+        names.sort();
+        return names;
+    }
+
+    // expectations listed first argument first, None for an unknown word
+    pub fn word_expectations(self: &Self, name: &str) -> Option<Vec<Expectation>> {
+        let word = self.program.cstack.words.get(name)?;
+        return Some(word.expectations.iter().rev().cloned().collect());
+    }
+
+    // what a defined word stands for, None for builtins and unknown words
+    pub fn definition(self: &Self, name: &str) -> Option<&Value> {
+        let word = self.program.cstack.words.get(name)?;
+        return word.substitution.as_ref();
+    }
+
+    pub fn forget(self: &mut Self, name: &str) -> bool {
+        return self.program.cstack.words.remove(name).is_some();
+    }
+
+    // the defined words as source that defines them again, one per line.
+    // define replaces a word that is already there
+    pub fn definitions_source(self: &Self) -> String {
+        let mut source = String::new();
+        for name in self.words() {
+            if let Some(value) = self.definition(&name) {
+                source = format!("{}define {} {}\n", source, name, format_source(value));
+            }
+        }
+        return source;
+    }
+
+    // back to only the builtins, words registered by the host are dropped too
+    pub fn reset_dictionary(self: &mut Self) -> () {
+        if self.suspended {
+            self.abort();
+        }
+        self.reset();
+        self.program.ostack.clear();
+        self.program.cstack.words.clear();
+        self.program.cstack.create_builtin_words();
+    }
+
+    pub fn reset(self: &mut Self) -> () {
+        self.suspended = false;
+        self.program.istack.clear();
+        self.program.cstack.stack.clear();
+    }
+}
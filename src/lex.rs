@@ -0,0 +1,751 @@
+use std::fmt;
+use std::iter::Peekable;
+use std::rc::Rc;
+
+use crate::error::StaplError;
+use crate::eval::Parameter;
+use crate::format::format_source;
+use crate::maps::{map_entries, map_items, Entries};
+use crate::number::Number;
+use crate::span::{Source, Span};
+
+#[derive(Debug, Copy, Clone)]
+pub enum Expectation {
+    Num,
+    Literal,
+    Stack,
+    Lambda,
+    Any,
+    Word,
+    Parameter,
+    NumStaLit,
+    Str,
+    Map,
+    // any evaluated value: words are evaluated first, lambdas are taken as they are
+    Value,
+}
+
+// the names expectations are shown with
+impl std::str::FromStr for Expectation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Expectation, String> {
+        match s {
+            "Num" => Ok(Expectation::Num),
+            "Literal" => Ok(Expectation::Literal),
+            "Stack" => Ok(Expectation::Stack),
+            "Lambda" => Ok(Expectation::Lambda),
+            "Any" => Ok(Expectation::Any),
+            "Word" => Ok(Expectation::Word),
+            "Parameter" => Ok(Expectation::Parameter),
+            "NumStaLit" => Ok(Expectation::NumStaLit),
+            "Str" => Ok(Expectation::Str),
+            "Map" => Ok(Expectation::Map),
+            "Value" => Ok(Expectation::Value),
+            _ => Err(format!("unknown expectation {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, PartialOrd)]
+pub enum LexItem {
+    Word(String, Span),
+    OpenParen(Span),
+    CloseParen(Span),
+    OpenBrace(Span),
+    CloseBrace(Span),
+    Num(Number, Span),
+    Parameter(String, Span),
+    Stack(Vec<LexItem>, Span),
+    Lambda(Vec<LexItem>, Span),
+    // a lambda that outlived the call it was written in, with the arguments of
+    // that call it refers to. the body is kept as written
+    Closure(Vec<LexItem>, Vec<Parameter>, Span),
+    Literal(String, Span),
+    Quote(Span),
+    Str(String, Span),
+    Map(Entries, Span),
+    Bool(bool, Span),
+    Nil(Span),
+}
+
+// spans are left out, use span() to see where an item came from
+impl fmt::Debug for LexItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexItem::Word(w, _) => f.debug_tuple("Word").field(w).finish(),
+            LexItem::OpenParen(_) => write!(f, "OpenParen"),
+            LexItem::CloseParen(_) => write!(f, "CloseParen"),
+            LexItem::OpenBrace(_) => write!(f, "OpenBrace"),
+            LexItem::CloseBrace(_) => write!(f, "CloseBrace"),
+            LexItem::Num(n, _) => f.debug_tuple("Num").field(n).finish(),
+            LexItem::Parameter(p, _) => f.debug_tuple("Parameter").field(p).finish(),
+            LexItem::Stack(s, _) => f.debug_tuple("Stack").field(s).finish(),
+            LexItem::Lambda(s, _) => f.debug_tuple("Lambda").field(s).finish(),
+            LexItem::Closure(s, captured, _) => {
+                f.debug_tuple("Closure").field(s).field(captured).finish()
+            }
+            LexItem::Literal(l, _) => f.debug_tuple("Literal").field(l).finish(),
+            LexItem::Quote(_) => write!(f, "Quote"),
+            LexItem::Str(s, _) => f.debug_tuple("Str").field(s).finish(),
+            LexItem::Map(m, _) => f.debug_tuple("Map").field(m).finish(),
+            LexItem::Bool(b, _) => f.debug_tuple("Bool").field(b).finish(),
+            LexItem::Nil(_) => write!(f, "Nil"),
+        }
+    }
+}
+
+impl LexItem {
+    pub fn span(self: &Self) -> &Span {
+        match self {
+            LexItem::Word(_, span)
+            | LexItem::OpenParen(span)
+            | LexItem::CloseParen(span)
+            | LexItem::OpenBrace(span)
+            | LexItem::CloseBrace(span)
+            | LexItem::Num(_, span)
+            | LexItem::Parameter(_, span)
+            | LexItem::Stack(_, span)
+            | LexItem::Lambda(_, span)
+            | LexItem::Closure(_, _, span)
+            | LexItem::Literal(_, span)
+            | LexItem::Quote(span)
+            | LexItem::Str(_, span)
+            | LexItem::Map(_, span)
+            | LexItem::Bool(_, span)
+            | LexItem::Nil(span) => {
+                return span;
+            }
+        }
+    }
+    pub fn get_Num(self: &Self) -> Option<Number> {
+        match self {
+            LexItem::Num(n, _) => {
+                return Some(n.clone());
+            }
+            _ => {
+                return None;
+            }
+        }
+    }
+    pub fn get_Word(self: &Self) -> Option<String> {
+        match self {
+            LexItem::Word(w, _) => {
+                return Some(w.to_string());
+            }
+            _ => {
+                return None;
+            }
+        }
+    }
+    pub(crate) fn get_arity(self: &Self) -> usize {
+        match self.get_parameters() {
+            Some(p) => {
+                return p.len();
+            }
+            None => {
+                return 0;
+            }
+        }
+    }
+    // the names arguments are bound to, in the order they are taken
+    pub(crate) fn get_parameters(self: &Self) -> Option<Vec<String>> {
+        match self {
+            LexItem::Lambda(s, _) => {
+                return Some(slot_order(s));
+            }
+            // the captured names are already bound
+            LexItem::Closure(s, captured, _) => {
+                let mut parameters = slot_order(s);
+                parameters.retain(|p| !captured.iter().any(|c| c.name == *p));
+                return Some(parameters);
+            }
+            _ => {
+                return None;
+            }
+        }
+    }
+    pub(crate) fn get_expectations<'e>(self: &Self) -> Vec<Expectation> {
+        let mut exp = Vec::new();
+
+        // lambdas are taken as they are so that they can be passed to higher-order words
+        if let Some(parameters) = self.get_parameters() {
+            for s in parameters {
+                exp.push(Expectation::Value);
+            }
+        }
+        return exp;
+    }
+    pub(crate) fn is_word(self: &Self, word: &str) -> bool {
+        if let LexItem::Word(w, _) = self {
+            return w == word;
+        }
+        return false;
+    }
+}
+
+// $1 up to the highest numbered parameter, then the named ones as they first appear
+fn slot_order(body: &Vec<LexItem>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    collect_parameters(body, &Vec::new(), &mut names, false);
+    let mut max = 0;
+    for name in names.iter() {
+        if let Ok(number) = name.parse::<usize>() {
+            if number > max {
+                max = number;
+            }
+        }
+    }
+    let mut parameters: Vec<String> = (1..max + 1).map(|n| n.to_string()).collect();
+    for name in names {
+        if !is_positional(&name) {
+            parameters.push(name);
+        }
+    }
+    return parameters;
+}
+
+// free parameters of a lambda body in the order they appear. a numbered parameter
+// belongs to the innermost lambda it is in, a named one to the outermost lambda that
+// mentions it, so the lambdas nested in a body only add their named parameters.
+// stacks and the branches of an if bind nothing and are searched like the body
+// around them. a name bound by "let $x value [ body ]" is not free in the lambdas
+// that follow the binder, bodies are stored last item first
+fn collect_parameters(
+    items: &Vec<LexItem>,
+    bound: &Vec<String>,
+    names: &mut Vec<String>,
+    nested: bool,
+) -> () {
+    let mut shadowed = bound.clone();
+    let mut after_let = false;
+    let branches = if_branches(items);
+    for (i, lexeme) in items.iter().enumerate().rev() {
+        match lexeme {
+            LexItem::Parameter(p, _) if after_let => {
+                shadowed.push(p.clone());
+            }
+            LexItem::Parameter(p, _) if nested && is_positional(p) => {}
+            LexItem::Parameter(p, _) => {
+                if !bound.contains(p) && !names.contains(p) {
+                    names.push(p.clone());
+                }
+            }
+            LexItem::Lambda(s, _) if branches.contains(&i) => {
+                collect_parameters(s, &shadowed, names, nested);
+            }
+            LexItem::Lambda(s, _) => {
+                collect_parameters(s, &shadowed, names, true);
+            }
+            LexItem::Stack(s, _) => {
+                collect_parameters(s, &shadowed, names, nested);
+            }
+            LexItem::Map(m, _) => {
+                collect_parameters(&map_items(m), &shadowed, names, nested);
+            }
+            _ => {}
+        }
+        after_let = lexeme.is_word("let");
+    }
+}
+
+// where the branches of the ifs in a body are: the first two lambdas in a row after
+// an if, read in source order. bodies are stored last item first
+pub(crate) fn if_branches(items: &Vec<LexItem>) -> Vec<usize> {
+    let mut branches = Vec::new();
+    let mut pending = 0;
+    let mut first: Option<usize> = None;
+    for (i, lexeme) in items.iter().enumerate().rev() {
+        match lexeme {
+            LexItem::Lambda(_, _) if pending > 0 => match first {
+                Some(j) => {
+                    branches.push(j);
+                    branches.push(i);
+                    pending = pending - 1;
+                    first = None;
+                }
+                None => {
+                    first = Some(i);
+                }
+            },
+            lexeme => {
+                if lexeme.is_word("if") {
+                    pending = pending + 1;
+                }
+                first = None;
+            }
+        }
+    }
+    return branches;
+}
+
+// parameters named by a number are bound by position to the innermost lambda
+pub(crate) fn is_positional(name: &str) -> bool {
+    return name.parse::<usize>().is_ok();
+}
+
+// the named parameters a value leaves free, those are the ones a let it is
+// substituted under could capture. closures are never substituted into
+pub(crate) fn free_names(value: &LexItem) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    match value {
+        LexItem::Lambda(s, _) | LexItem::Stack(s, _) => {
+            collect_parameters(s, &Vec::new(), &mut names, false);
+        }
+        LexItem::Map(m, _) => {
+            collect_parameters(&map_items(m), &Vec::new(), &mut names, false);
+        }
+        LexItem::Parameter(p, _) => {
+            names.push(p.clone());
+        }
+        _ => {}
+    }
+    names.retain(|n| !is_positional(n));
+    return names;
+}
+
+// errors are collected rather than returned so that tools reading unfinished
+// input, like the REPL's highlighter, still get every lexeme
+pub(crate) fn next_lexeme<T: Iterator<Item = (usize, char)>>(
+    mut it: &mut Peekable<T>,
+    source: &Rc<Source>,
+    errors: &mut Vec<StaplError>,
+) -> Option<LexItem> {
+    let (start, c) = *(it.peek().unwrap());
+
+    //println!("lex {}", c);
+
+    let lexeme = match c {
+        '0'...'9' | '-' | '.' => {
+            it.next();
+            let a = lex_word(c, &mut it);
+            let span = lex_span(it, source, start);
+            match Number::parse(&a) {
+                Some(n) => LexItem::Num(n, span),
+                // 1e400 reads as a float but has no finite value
+                None if a.parse::<f64>().map_or(false, |f| f.is_infinite()) => {
+                    errors.push(StaplError::Syntax {
+                        message: format!("number out of range: {}", a),
+                        span: span,
+                    });
+                    return None;
+                }
+                None if c == '.' => {
+                    errors.push(StaplError::Syntax {
+                        message: "unexpected character '.'".to_string(),
+                        span: span,
+                    });
+                    return None;
+                }
+                None => LexItem::Word(a, span),
+            }
+        }
+        'A'...'Z' | 'a'...'z' | '+' | '*' | '/' | '<' | '>' | '=' | '!' => {
+            it.next();
+            let a = lex_word(c, &mut it);
+            let span = lex_span(it, source, start);
+            match a.as_str() {
+                "true" => LexItem::Bool(true, span),
+                "false" => LexItem::Bool(false, span),
+                "nil" => LexItem::Nil(span),
+                _ => LexItem::Word(a, span),
+            }
+        }
+
+        '$' => {
+            it.next();
+            let p = lex_parameter(c, &mut it);
+            LexItem::Parameter(p, lex_span(it, source, start))
+        }
+        '#' => {
+            it.next();
+            LexItem::Word("#".to_string(), lex_span(it, source, start))
+        }
+        c if c.is_whitespace() => {
+            it.next();
+            return None;
+        }
+        '{' => {
+            it.next();
+            LexItem::OpenBrace(lex_span(it, source, start))
+        }
+        '}' => {
+            it.next();
+            LexItem::CloseBrace(lex_span(it, source, start))
+        }
+        '[' | '(' => {
+            it.next();
+            LexItem::OpenParen(lex_span(it, source, start))
+        }
+        ']' | ')' => {
+            it.next();
+            LexItem::CloseParen(lex_span(it, source, start))
+        }
+        '\'' => {
+            it.next();
+            LexItem::Quote(lex_span(it, source, start))
+        }
+        '"' => {
+            it.next();
+            let s = lex_string(&mut it, source, errors);
+            let span = lex_span(it, source, start);
+            if !is_closed_string(&source.text[span.start..span.end]) {
+                errors.push(StaplError::Syntax {
+                    message: "unterminated string".to_string(),
+                    span: span.clone(),
+                });
+            }
+            LexItem::Str(s, span)
+        }
+        _ => {
+            it.next();
+            errors.push(StaplError::Syntax {
+                message: format!("unexpected character '{}'", c),
+                span: lex_span(it, source, start),
+            });
+            return None;
+        }
+    };
+    return Some(lexeme);
+}
+
+// the span from start up to the next unread character
+fn lex_span<T: Iterator<Item = (usize, char)>>(
+    it: &mut Peekable<T>,
+    source: &Rc<Source>,
+    start: usize,
+) -> Span {
+    let end = match it.peek() {
+        Some((offset, _)) => *offset,
+        None => source.text.len(),
+    };
+    return Span::new(source, start, end);
+}
+
+pub(crate) fn lex_parameter<T: Iterator<Item = (usize, char)>>(
+    inc: char,
+    iter: &mut Peekable<T>,
+) -> String {
+    let mut name = String::new();
+    while let Some(&(_, c)) = iter.peek() {
+        if !(c.is_alphanumeric() || c == '_') {
+            break;
+        }
+        name.push(c);
+        iter.next();
+    }
+    // positional parameters are plain numbers, $01 is $1
+    if let Ok(number) = name.parse::<usize>() {
+        return number.to_string();
+    }
+    if name.len() == 0 {
+        return "0".to_string();
+    }
+    return name;
+}
+
+pub(crate) fn lex_word<T: Iterator<Item = (usize, char)>>(
+    c: char,
+    iter: &mut Peekable<T>,
+) -> String {
+    let mut word = c.to_string();
+    //println!("word c: {}", c);
+    while let Some(&(_, letter)) = iter.peek() {
+        //println!("word peek: {}", letter);
+        if letter.is_whitespace() || letter == ']' || letter == ')' || letter == '}' {
+            break;
+        }
+
+        word.push(letter);
+        iter.next();
+    }
+    //println!("get_word {}", word);
+    word
+}
+
+// reads up to and including the closing quote, an unterminated string runs to the end of input
+pub(crate) fn lex_string<T: Iterator<Item = (usize, char)>>(
+    iter: &mut Peekable<T>,
+    source: &Rc<Source>,
+    errors: &mut Vec<StaplError>,
+) -> String {
+    let mut string = String::new();
+    while let Some((offset, c)) = iter.next() {
+        match c {
+            '"' => {
+                break;
+            }
+            '\\' => match iter.next() {
+                Some((_, '"')) => string.push('"'),
+                Some((_, '\\')) => string.push('\\'),
+                Some((_, 'n')) => string.push('\n'),
+                Some((_, 't')) => string.push('\t'),
+                Some((_, 'r')) => string.push('\r'),
+                Some((_, '0')) => string.push('\0'),
+                Some((_, 'u')) => match lex_unicode_escape(iter) {
+                    Some(ch) => string.push(ch),
+                    None => {
+                        errors.push(StaplError::Syntax {
+                            message: "malformed unicode escape, expected \\u{hex}".to_string(),
+                            span: lex_span(iter, source, offset),
+                        });
+                    }
+                },
+                Some((_, e)) => {
+                    errors.push(StaplError::Syntax {
+                        message: format!("unknown escape \\{}", e),
+                        span: lex_span(iter, source, offset),
+                    });
+                }
+                // the string is unterminated, which is reported for the whole of it
+                None => {}
+            },
+            _ => {
+                string.push(c);
+            }
+        }
+    }
+    return string;
+}
+
+// the rest of a \u{..} escape, None when it is malformed
+fn lex_unicode_escape<T: Iterator<Item = (usize, char)>>(iter: &mut Peekable<T>) -> Option<char> {
+    if iter.peek().map(|(_, c)| *c) != Some('{') {
+        return None;
+    }
+    iter.next();
+    let mut hex = String::new();
+    while let Some(&(_, c)) = iter.peek() {
+        if c == '}' {
+            iter.next();
+            return u32::from_str_radix(&hex, 16)
+                .ok()
+                .and_then(std::char::from_u32);
+        }
+        if !c.is_ascii_hexdigit() || hex.len() >= 6 {
+            return None;
+        }
+        hex.push(c);
+        iter.next();
+    }
+    return None;
+}
+
+// parses up to the bracket that closes a nested stack, which is left on top of it
+pub(crate) fn parse_stacks<'i>(
+    lex_input: &mut Vec<LexItem>,
+    parsed_input: &'i mut Vec<LexItem>,
+    nested: bool,
+) -> Result<&'i mut Vec<LexItem>, StaplError> {
+    let mut quoted: Option<Span> = None;
+    while let Some(itop) = lex_input.pop() {
+        match itop {
+            LexItem::OpenParen(open) => {
+                //println!("openbracket found");
+                let mut newstack = &mut Vec::new();
+                newstack = parse_stacks(lex_input, newstack, true)?;
+                let span = match newstack.first() {
+                    Some(LexItem::CloseParen(close)) => open.join(close),
+                    Some(LexItem::CloseBrace(close)) => {
+                        return Err(StaplError::Syntax {
+                            message: "} closes [".to_string(),
+                            span: close.clone(),
+                        });
+                    }
+                    _ => {
+                        return Err(StaplError::Syntax {
+                            message: "unclosed [".to_string(),
+                            span: open,
+                        });
+                    }
+                };
+                newstack.remove(0);
+                match quoted.take() {
+                    Some(q) => {
+                        parsed_input.insert(0, LexItem::Stack(newstack.to_vec(), q.join(&span)));
+                    }
+                    None => {
+                        parsed_input.insert(0, LexItem::Lambda(newstack.to_vec(), span));
+                    }
+                }
+            }
+            // a map literal holds its items as written, paired up as keys and values
+            LexItem::OpenBrace(open) => {
+                let mut newstack = &mut Vec::new();
+                newstack = parse_stacks(lex_input, newstack, true)?;
+                let span = match newstack.first() {
+                    Some(LexItem::CloseBrace(close)) => open.join(close),
+                    Some(LexItem::CloseParen(close)) => {
+                        return Err(StaplError::Syntax {
+                            message: "] closes {".to_string(),
+                            span: close.clone(),
+                        });
+                    }
+                    _ => {
+                        return Err(StaplError::Syntax {
+                            message: "unclosed {".to_string(),
+                            span: open,
+                        });
+                    }
+                };
+                newstack.remove(0);
+                quoted = None;
+                // the items are not evaluated, so a word would end up as a key or value
+                for item in newstack.iter().rev() {
+                    if let LexItem::Word(w, span) = item {
+                        return Err(StaplError::Syntax {
+                            message: format!("word in a map literal: {}", w),
+                            span: span.clone(),
+                        });
+                    }
+                }
+                match map_entries(newstack.to_vec()) {
+                    Ok(entries) => {
+                        parsed_input.insert(0, LexItem::Map(entries, span));
+                    }
+                    Err(key) => {
+                        return Err(StaplError::Syntax {
+                            message: format!("map key without a value: {}", format_source(&key)),
+                            span: key.span().clone(),
+                        });
+                    }
+                }
+            }
+            LexItem::CloseParen(close) if !nested => {
+                return Err(StaplError::Syntax {
+                    message: "unmatched ]".to_string(),
+                    span: close,
+                });
+            }
+            LexItem::CloseBrace(close) if !nested => {
+                return Err(StaplError::Syntax {
+                    message: "unmatched }".to_string(),
+                    span: close,
+                });
+            }
+            LexItem::CloseParen(close) => {
+                //println!("closedbracket found");
+                parsed_input.insert(0, LexItem::CloseParen(close));
+                return Ok(parsed_input);
+            }
+            LexItem::CloseBrace(close) => {
+                parsed_input.insert(0, LexItem::CloseBrace(close));
+                return Ok(parsed_input);
+            }
+            LexItem::Quote(q) => {
+                quoted = Some(q);
+            }
+            LexItem::Word(w, span) => {
+                match quoted.take() {
+                    Some(q) => {
+                        parsed_input.insert(0, LexItem::Literal(w.to_string(), q.join(&span)));
+                    }
+                    None => {
+                        //println!("lexeme found: {}", print_lexeme(&itop));
+                        parsed_input.insert(0, LexItem::Word(w, span));
+                    }
+                }
+            }
+            // 'true is the literal, not the boolean
+            LexItem::Bool(..) | LexItem::Nil(..) if quoted.is_some() => {
+                let q = quoted.take().unwrap();
+                let name = match &itop {
+                    LexItem::Bool(b, _) => b.to_string(),
+                    _ => "nil".to_string(),
+                };
+                parsed_input.insert(0, LexItem::Literal(name, q.join(itop.span())));
+            }
+            _ => {
+                //println!("lexeme found: {}", print_lexeme(&itop));
+                quoted = None;
+                parsed_input.insert(0, itop);
+            }
+        }
+    }
+    return Ok(parsed_input);
+}
+
+// the first error in the source is reported
+pub(crate) fn parse_input<'i>(
+    source: &Rc<Source>,
+    lexstack: &'i mut Vec<LexItem>,
+) -> Result<&'i mut Vec<LexItem>, StaplError> {
+    let mut errors = Vec::new();
+    if source.text.len() > 0 {
+        //lex all of the input
+        let mut it = source.text.char_indices().peekable();
+        while it.peek() != None {
+            match next_lexeme(&mut it, source, &mut errors) {
+                Some(lexeme) => {
+                    lexstack.insert(0, lexeme);
+                }
+                _ => (),
+            }
+        }
+    }
+    if errors.len() > 0 {
+        return Err(errors.remove(0));
+    }
+    return Ok(lexstack);
+}
+
+// the lexemes of a piece of source in the order they were written, for
+// tools like the REPL that look at input without parsing it. errors are ignored
+pub fn lex(text: &str) -> Vec<LexItem> {
+    let mut lexemes = Vec::new();
+    let mut errors = Vec::new();
+    let source = Rc::new(Source::new("input", text));
+    let mut it = source.text.char_indices().peekable();
+    while it.peek() != None {
+        if let Some(lexeme) = next_lexeme(&mut it, &source, &mut errors) {
+            lexemes.push(lexeme);
+        }
+    }
+    return lexemes;
+}
+
+// the values a piece of source is made of, in the order they were written
+pub fn parse(text: &str) -> Result<Vec<LexItem>, StaplError> {
+    let source = Rc::new(Source::new("input", text));
+    let mut lexstack = Vec::new();
+    parse_input(&source, &mut lexstack)?;
+    let mut parsed = Vec::new();
+    parse_stacks(&mut lexstack, &mut parsed, false)?;
+    parsed.reverse();
+    return Ok(parsed);
+}
+
+// whether every bracket and string has been closed. a closing bracket that
+// nothing opened can't be fixed by more input, the parser reports it
+pub fn is_balanced(text: &str) -> bool {
+    let mut depth = 0;
+    for lexeme in lex(text) {
+        match lexeme {
+            LexItem::OpenParen(_) | LexItem::OpenBrace(_) => {
+                depth += 1;
+            }
+            LexItem::CloseParen(_) | LexItem::CloseBrace(_) => {
+                depth -= 1;
+                if depth < 0 {
+                    return true;
+                }
+            }
+            LexItem::Str(_, span) => {
+                if !is_closed_string(&text[span.start..span.end]) {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+    }
+    return depth == 0;
+}
+
+// a string runs to the end of input unless it ends in a quote that isn't escaped
+fn is_closed_string(written: &str) -> bool {
+    if written.len() < 2 || !written.ends_with('"') {
+        return false;
+    }
+    let body = &written[..written.len() - 1];
+    let escapes = body.len() - body.trim_end_matches('\\').len();
+    return escapes % 2 == 0;
+}
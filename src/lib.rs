@@ -0,0 +1,31 @@
+mod arithmetic;
+mod builtins;
+mod debug;
+mod error;
+mod eval;
+mod format;
+mod image;
+mod interpreter;
+mod lex;
+mod limits;
+mod lists;
+mod maps;
+mod number;
+mod shuffle;
+mod span;
+mod strings;
+mod trace;
+
+pub use crate::debug::{DebugCommand, DebugStop};
+pub use crate::error::StaplError;
+pub use crate::eval::{Action, Call, Env, Parameter, Pending};
+pub use crate::format::{
+    format_diagnostic, format_lexstack, format_source, format_values, print_lexeme,
+};
+pub use crate::interpreter::{Interpreter, Value};
+pub use crate::lex::{is_balanced, lex, parse, Expectation, LexItem};
+pub use crate::limits::{EvalLimits, Limit};
+pub use crate::maps::Entries;
+pub use crate::number::Number;
+pub use crate::span::{Source, Span};
+pub use crate::trace::{TraceEvent, TraceFormat, TraceLevel, TraceSink};
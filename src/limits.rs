@@ -0,0 +1,108 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// resource limits for a single evaluation, None leaves that resource unbounded
+#[derive(Debug, Clone, Copy)]
+pub struct EvalLimits {
+    pub max_steps: Option<u64>,
+    pub max_call_depth: Option<usize>,
+    pub max_stack_items: Option<usize>,
+    pub timeout: Option<Duration>,
+}
+
+impl EvalLimits {
+    pub fn unlimited() -> EvalLimits {
+        return EvalLimits {
+            max_steps: None,
+            max_call_depth: None,
+            max_stack_items: None,
+            timeout: None,
+        };
+    }
+}
+
+impl Default for EvalLimits {
+    fn default() -> EvalLimits {
+        return EvalLimits {
+            max_steps: Some(1_000_000),
+            max_call_depth: Some(10_000),
+            max_stack_items: Some(1_000_000),
+            timeout: None,
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Limit {
+    Steps(u64),
+    CallDepth(usize),
+    StackItems(usize),
+    Timeout(Duration),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Limit::Steps(n) => write!(f, "more than {} evaluation steps", n),
+            Limit::CallDepth(n) => write!(f, "more than {} nested calls", n),
+            Limit::StackItems(n) => write!(f, "more than {} stack items", n),
+            Limit::Timeout(d) => write!(f, "ran longer than {:?}", d),
+        }
+    }
+}
+
+// what one evaluation has used so far, restarted when it is resumed
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Budget {
+    pub(crate) steps: u64,
+    pub(crate) started: Instant,
+    // set when a limit stopped the evaluation between steps, which it can resume from
+    pub(crate) stopped: bool,
+}
+
+impl Budget {
+    pub(crate) fn new() -> Budget {
+        return Budget {
+            steps: 0,
+            started: Instant::now(),
+            stopped: false,
+        };
+    }
+
+    // counts a step and returns the first limit it goes over
+    pub(crate) fn step(
+        self: &mut Self,
+        limits: &EvalLimits,
+        depth: usize,
+        items: usize,
+    ) -> Option<Limit> {
+        self.steps = self.steps + 1;
+        if let Some(max) = limits.max_steps {
+            if self.steps > max {
+                return Some(Limit::Steps(max));
+            }
+        }
+        if let Some(max) = limits.max_call_depth {
+            if depth > max {
+                return Some(Limit::CallDepth(max));
+            }
+        }
+        return self.check(limits, items);
+    }
+
+    // the limits a builtin can run into while it is applied: the items it
+    // would leave on the stacks and the time taken so far
+    pub(crate) fn check(self: &Self, limits: &EvalLimits, items: usize) -> Option<Limit> {
+        if let Some(max) = limits.max_stack_items {
+            if items > max {
+                return Some(Limit::StackItems(max));
+            }
+        }
+        if let Some(max) = limits.timeout {
+            if self.started.elapsed() > max {
+                return Some(Limit::Timeout(max));
+            }
+        }
+        return None;
+    }
+}
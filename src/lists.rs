@@ -0,0 +1,384 @@
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use crate::builtins::check_conditional;
+use crate::error::StaplError;
+use crate::eval::{Call, CallStack, Env};
+use crate::lex::{Expectation, LexItem};
+use crate::number::Number;
+
+// stacks keep their first element last, so the element a word calls first is
+// the one pop() gives
+impl CallStack {
+    pub(crate) fn create_list_words(self: &mut Self) -> () {
+        self.add_builtin("len", vec![Expectation::NumStaLit], Rc::new(action_len));
+        self.add_builtin(
+            "concat",
+            vec![Expectation::NumStaLit, Expectation::NumStaLit],
+            Rc::new(action_concat),
+        );
+        self.add_builtin(
+            "push",
+            vec![Expectation::Value, Expectation::Stack],
+            Rc::new(action_push),
+        );
+        self.add_builtin("pop", vec![Expectation::Stack], Rc::new(action_pop));
+        self.add_builtin(
+            "cons",
+            vec![Expectation::Stack, Expectation::Value],
+            Rc::new(action_cons),
+        );
+        self.add_builtin("head", vec![Expectation::Stack], Rc::new(action_head));
+        self.add_builtin("tail", vec![Expectation::Stack], Rc::new(action_tail));
+        self.add_builtin("reverse", vec![Expectation::Stack], Rc::new(action_reverse));
+        self.add_builtin(
+            "slice",
+            vec![Expectation::Num, Expectation::Num, Expectation::Stack],
+            Rc::new(action_slice),
+        );
+        self.add_builtin(
+            "range",
+            vec![Expectation::Num, Expectation::Num],
+            Rc::new(action_range),
+        );
+        self.add_builtin("sort", vec![Expectation::Stack], Rc::new(action_sort));
+        self.add_builtin(
+            "zip",
+            vec![Expectation::Stack, Expectation::Stack],
+            Rc::new(action_zip),
+        );
+        self.add_builtin("flatten", vec![Expectation::Stack], Rc::new(action_flatten));
+
+        // the higher-order words apply their lambda by returning it with an element
+        // and asking for one more argument, which the application then supplies
+        self.add_builtin(
+            "map",
+            vec![Expectation::Stack, Expectation::Lambda],
+            Rc::new(action_map),
+        );
+        self.add_builtin(
+            "filter",
+            vec![Expectation::Stack, Expectation::Lambda],
+            Rc::new(action_filter),
+        );
+        self.add_builtin(
+            "fold",
+            vec![Expectation::Stack, Expectation::Value, Expectation::Lambda],
+            Rc::new(action_fold),
+        );
+    }
+}
+
+fn push_stack(call: &mut Call, stack: Vec<LexItem>) -> () {
+    call.push_result(LexItem::Stack(stack, call.span().clone()));
+}
+fn underflow(call: &Call) -> StaplError {
+    return StaplError::StackUnderflow {
+        word: call.name.to_string(),
+        span: call.span().clone(),
+    };
+}
+
+// len and concat work on strings as well as stacks
+pub(crate) fn action_len(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let value = call.pop_argument()?;
+    let len = match &value {
+        LexItem::Str(s, _) => s.chars().count(),
+        LexItem::Stack(s, _) => s.len(),
+        _ => {
+            return Err(call.type_mismatch(Expectation::Stack, value));
+        }
+    };
+    call.push_result(LexItem::Num(Number::from(len as i64), call.span().clone()));
+    return Ok(());
+}
+pub(crate) fn action_concat(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let a = call.pop_argument()?;
+    let b = call.pop_argument()?;
+    match (a, b) {
+        (LexItem::Str(a, _), LexItem::Str(b, _)) => {
+            // a character counts as an item, so doubling a string can't go on forever
+            env.check_items(a.len() + b.len(), call.span())?;
+            call.push_result(LexItem::Str(a + &b, call.span().clone()));
+        }
+        (LexItem::Stack(a, _), LexItem::Stack(mut b, _)) => {
+            env.check_items(a.len() + b.len(), call.span())?;
+            b.extend(a);
+            push_stack(call, b);
+        }
+        (LexItem::Str(..), b) => {
+            return Err(call.type_mismatch(Expectation::Str, b));
+        }
+        (LexItem::Stack(..), b) => {
+            return Err(call.type_mismatch(Expectation::Stack, b));
+        }
+        (a, _) => {
+            return Err(call.type_mismatch(Expectation::Stack, a));
+        }
+    }
+    return Ok(());
+}
+// push stack value: the value added after the last element
+pub(crate) fn action_push(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut s = call.pop_stack()?;
+    let value = call.pop_argument()?;
+    s.insert(0, value);
+    push_stack(call, s);
+    return Ok(());
+}
+// pop stack: the last element, then the stack without it
+pub(crate) fn action_pop(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut s = call.pop_stack()?;
+    if s.len() == 0 {
+        return Err(underflow(call));
+    }
+    let last = s.remove(0);
+    push_stack(call, s);
+    call.push_result(last);
+    return Ok(());
+}
+// cons value stack: the value added before the first element
+pub(crate) fn action_cons(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let value = call.pop_argument()?;
+    let mut s = call.pop_stack()?;
+    s.push(value);
+    push_stack(call, s);
+    return Ok(());
+}
+pub(crate) fn action_head(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut s = call.pop_stack()?;
+    match s.pop() {
+        Some(first) => {
+            call.push_result(first);
+        }
+        None => {
+            return Err(underflow(call));
+        }
+    }
+    return Ok(());
+}
+pub(crate) fn action_tail(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut s = call.pop_stack()?;
+    if s.pop().is_none() {
+        return Err(underflow(call));
+    }
+    push_stack(call, s);
+    return Ok(());
+}
+pub(crate) fn action_reverse(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut s = call.pop_stack()?;
+    s.reverse();
+    push_stack(call, s);
+    return Ok(());
+}
+// slice stack start length, counted from 0 like substr
+pub(crate) fn action_slice(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut s = call.pop_stack()?;
+    let start = call.pop_int()?;
+    let length = call.pop_int()?;
+    let len = s.len();
+    if start < 0 || start as usize > len {
+        return Err(StaplError::IndexOutOfRange {
+            word: call.name.to_string(),
+            index: start,
+            len: len,
+            span: call.span().clone(),
+        });
+    }
+    if length < 0 || length as usize > len - start as usize {
+        return Err(StaplError::IndexOutOfRange {
+            word: call.name.to_string(),
+            index: start.saturating_add(length),
+            len: len,
+            span: call.span().clone(),
+        });
+    }
+    s.reverse();
+    let mut sub: Vec<LexItem> = s
+        .into_iter()
+        .skip(start as usize)
+        .take(length as usize)
+        .collect();
+    sub.reverse();
+    push_stack(call, sub);
+    return Ok(());
+}
+// range from to: the integers from up to but not including to
+pub(crate) fn action_range(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let from = call.pop_int()?;
+    let to = call.pop_int()?;
+    let span = call.span().clone();
+    if to > from {
+        env.check_items(to.saturating_sub(from) as usize, &span)?;
+    }
+    let mut s: Vec<LexItem> = (from..to)
+        .map(|n| LexItem::Num(Number::from(n), span.clone()))
+        .collect();
+    s.reverse();
+    push_stack(call, s);
+    return Ok(());
+}
+// numbers or strings in ascending order, equal elements keep their order
+pub(crate) fn action_sort(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut s = call.pop_stack()?;
+    for item in s.iter() {
+        let sortable = match (item, s.last()) {
+            (LexItem::Num(..), Some(LexItem::Num(..))) => true,
+            (LexItem::Str(..), Some(LexItem::Str(..))) => true,
+            _ => false,
+        };
+        if !sortable {
+            return Err(call.type_mismatch(Expectation::Num, item.clone()));
+        }
+    }
+    s.reverse();
+    s.sort_by(|a, b| match (a, b) {
+        (LexItem::Num(a, _), LexItem::Num(b, _)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+        (LexItem::Str(a, _), LexItem::Str(b, _)) => a.cmp(b),
+        _ => Ordering::Equal,
+    });
+    s.reverse();
+    push_stack(call, s);
+    return Ok(());
+}
+// zip a b: a stack of two element stacks, as long as the shorter one
+pub(crate) fn action_zip(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut a = call.pop_stack()?;
+    let mut b = call.pop_stack()?;
+    let span = call.span().clone();
+    let mut pairs = Vec::new();
+    while let (Some(x), Some(y)) = (a.pop(), b.pop()) {
+        pairs.insert(0, LexItem::Stack(vec![y, x], span.clone()));
+    }
+    push_stack(call, pairs);
+    return Ok(());
+}
+// one level: the elements of stacks in the stack take their place
+pub(crate) fn action_flatten(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let s = call.pop_stack()?;
+    let mut flat = Vec::new();
+    for item in s {
+        match item {
+            LexItem::Stack(inner, _) => {
+                flat.extend(inner);
+            }
+            item => {
+                flat.push(item);
+            }
+        }
+    }
+    push_stack(call, flat);
+    return Ok(());
+}
+
+// the lambda given to a higher-order word must take as many arguments as it passes
+fn check_lambda(call: &Call, lambda: &LexItem, arity: usize) -> Result<(), StaplError> {
+    if lambda.get_arity() != arity {
+        return Err(StaplError::Arity {
+            word: call.name.to_string(),
+            expected: arity,
+            found: lambda.get_arity(),
+            span: lambda.span().clone(),
+        });
+    }
+    return Ok(());
+}
+// returns "lambda arguments.." for evaluation and asks for its value, the
+// kept values are popped first when the call is applied again
+fn apply_next(call: &mut Call, lambda: LexItem, arguments: Vec<LexItem>, kept: Vec<LexItem>) -> () {
+    for value in kept.into_iter().rev() {
+        call.keep_argument(value);
+    }
+    for value in arguments.into_iter().rev() {
+        call.push_result(value);
+    }
+    call.push_result(lambda);
+    call.expectations.push(Expectation::NumStaLit);
+}
+
+// map lambda stack: the stack of the lambda's value for each element
+pub(crate) fn action_map(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let lambda = call.pop_argument()?;
+    let mut s = call.pop_stack()?;
+    let mut mapped = Vec::new();
+    if !call.ready {
+        check_lambda(call, &lambda, 1)?;
+        call.ready = true;
+    } else {
+        mapped = call.pop_stack()?;
+        mapped.insert(0, call.pop_argument()?);
+    }
+    match s.pop() {
+        Some(next) => {
+            let span = call.span().clone();
+            let kept = vec![
+                lambda.clone(),
+                LexItem::Stack(s, span.clone()),
+                LexItem::Stack(mapped, span),
+            ];
+            apply_next(call, lambda, vec![next], kept);
+        }
+        None => {
+            push_stack(call, mapped);
+        }
+    }
+    return Ok(());
+}
+// filter lambda stack: the elements the lambda is true for
+pub(crate) fn action_filter(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let lambda = call.pop_argument()?;
+    let mut s = call.pop_stack()?;
+    let mut kept = Vec::new();
+    if !call.ready {
+        check_lambda(call, &lambda, 1)?;
+        call.ready = true;
+    } else {
+        kept = call.pop_stack()?;
+        let element = call.pop_argument()?;
+        if check_conditional(call.pop_argument()?) {
+            kept.insert(0, element);
+        }
+    }
+    match s.pop() {
+        Some(next) => {
+            let span = call.span().clone();
+            let state = vec![
+                lambda.clone(),
+                LexItem::Stack(s, span.clone()),
+                LexItem::Stack(kept, span),
+                next.clone(),
+            ];
+            apply_next(call, lambda, vec![next], state);
+        }
+        None => {
+            push_stack(call, kept);
+        }
+    }
+    return Ok(());
+}
+// fold lambda initial stack: the lambda applied to the value so far and each
+// element in turn, starting from the initial value
+pub(crate) fn action_fold(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let lambda = call.pop_argument()?;
+    let mut s;
+    let value;
+    if !call.ready {
+        check_lambda(call, &lambda, 2)?;
+        call.ready = true;
+        value = call.pop_argument()?;
+        s = call.pop_stack()?;
+    } else {
+        s = call.pop_stack()?;
+        value = call.pop_argument()?;
+    }
+    match s.pop() {
+        Some(next) => {
+            let kept = vec![lambda.clone(), LexItem::Stack(s, call.span().clone())];
+            apply_next(call, lambda, vec![value, next], kept);
+        }
+        None => {
+            call.push_result(value);
+        }
+    }
+    return Ok(());
+}
@@ -1,1023 +1,478 @@
-//use rustyline::error::ReadlineError;
+use rustyline::error::ReadlineError;
 use rustyline::Editor;
-use std::collections::HashMap;
 use std::env;
-use std::fmt;
-use std::iter::Peekable;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::time::Duration;
+
+use third::{
+    format_diagnostic, format_source, format_values, DebugCommand, DebugStop, EvalLimits,
+    Interpreter, StaplError, TraceFormat, TraceLevel,
+};
 
 extern crate rustyline;
 
-fn format_lexstack(stack: &Vec<LexItem>) -> String {
-    let mut result = "".to_string();
-    let stack_iter = stack.iter().rev();
-    if stack_iter.len() == 0 {
-        result = format!("None");
-    }
-    for i in stack_iter {
-        match i {
-            LexItem::Stack(s) => {
-                result = format!("{}'[ {}] ", result, format_lexstack(s));
-            }
-            LexItem::Lambda(s) => {
-                result = format!("{}[ {}] ", result, format_lexstack(s));
-            }
+mod helper;
 
-            _ => {
-                result = format!("{}{:?} ", result, i);
-            }
-        }
-    }
-    return result;
-}
-fn format_expstack(stack: &Vec<Expectation>) -> String {
-    let mut result = "".to_string();
-    let stack_iter = stack.iter().rev();
-    for i in stack_iter {
-        result = format!("{}{:?} ", result, i);
+use crate::helper::{describe, ReplHelper};
+
+fn format_position(interpreter: &Interpreter) -> String {
+    let names = interpreter.backtrace();
+    if names.len() == 0 {
+        return "top level".to_string();
     }
-    return result;
+    return names.join(" in ");
 }
 
-fn format_parstack(stack: &Vec<Parameter>) -> String {
-    let mut result = "".to_string();
-    let stack_iter = stack.iter().rev();
-    if stack_iter.len() == 0 {
-        result = format!("None");
-    }
-    for i in stack_iter {
-        match &i.value {
-            LexItem::Stack(s) => {
-                result = format!("{}({})[ {}] ", result, i.name, format_lexstack(&s));
-            }
-            LexItem::Lambda(s) => {
-                result = format!("{}({})[ {}] ", result, i.name, format_lexstack(&s));
-            }
-            _ => {
-                result = format!("{}{:?} ", result, i);
-            }
-        }
-    }
-    return result;
+fn report_error(interpreter: &Interpreter, error: StaplError) -> () {
+    eprintln!("{}", format_diagnostic(&error));
+    eprintln!("  at: {}", format_position(interpreter));
 }
 
-#[derive(Debug, Copy, Clone)]
-enum Expectation {
-    Num,
-    Literal,
-    Stack,
-    Lambda,
-    Any,
-    Word,
-    Parameter,
-    NumStaLit,
+const USAGE: &str = "usage: third [--max-steps N] [--max-depth N] [--max-items N] [--timeout SECONDS] [--trace LEVEL] [--trace-format text|json] [FILE | -e EXPR]...";
+
+struct Options {
+    limits: EvalLimits,
+    trace: TraceLevel,
+    trace_format: TraceFormat,
+    sources: Vec<(String, String)>,
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
-enum LexItem {
-    Word(String),
-    OpenParen,
-    CloseParen,
-    Num(i64),
-    #[derive(Ord)]
-    Parameter(String),
-    Stack(Vec<LexItem>),
-    Lambda(Vec<LexItem>),
-    Literal(String),
-    Quote,
+impl Options {
+    fn configure(self: &Self, interpreter: &mut Interpreter) -> () {
+        interpreter.set_limits(self.limits);
+        interpreter.set_trace_level(self.trace);
+        interpreter.set_trace_format(self.trace_format);
+    }
 }
 
-impl LexItem {
-    fn get_Num(self: &Self) -> Option<i64> {
-        match self {
-            LexItem::Num(n) => {
-                return Some(*n);
-            }
-            _ => {
-                return None;
-            }
+fn parse_value<T: std::str::FromStr<Err = String>>(value: Option<String>) -> Result<T, String> {
+    match value {
+        Some(v) => {
+            return v.parse::<T>();
         }
-    }
-    fn get_Word(self: &Self) -> Option<String> {
-        match self {
-            LexItem::Word(w) => {
-                return Some(w.to_string());
-            }
-            _ => {
-                return None;
-            }
+        None => {
+            return Err(USAGE.to_string());
         }
     }
-    fn get_arity(self: &Self) -> usize {
-        let mut arity = 0;
-        match self {
-            LexItem::Lambda(s) => {
-                let parameters = self.get_parameters();
-                match parameters {
-                    Some(mut p) => {
-                        let mut max = 0;
-                        let len = p.len();
-
-                        for parameter in p {
-                            if let Ok(number) = parameter.parse::<usize>() {
-                                if number > max {
-                                    max = number;
-                                }
-                            }
-                        }
+}
 
-                        arity = max;
-                    }
-                    None => {}
-                }
+// a limit given on the command line, "none" lifts it
+fn parse_limit<T: std::str::FromStr>(
+    flag: &str,
+    value: Option<String>,
+) -> Result<Option<T>, String> {
+    match value {
+        Some(v) => {
+            if v == "none" {
+                return Ok(None);
             }
-            _ => {}
-        }
-        println!("get_arity {}", arity);
-        return arity;
-    }
-    fn get_parameters(self: &Self) -> Option<Vec<String>> {
-        match self {
-            LexItem::Lambda(s) => {
-                let mut parameters: Vec<String> = Vec::new();
-                for lexeme in s {
-                    if let LexItem::Parameter(p) = lexeme {
-                        parameters.push(p.clone());
-                    }
+            match v.parse::<T>() {
+                Ok(n) => {
+                    return Ok(Some(n));
+                }
+                Err(_) => {
+                    return Err(format!("{}: invalid limit {}", flag, v));
                 }
-                parameters.sort_unstable();
-                parameters.dedup();
-                println!("get_parameters {:?}", parameters);
-                return Some(parameters);
-            }
-            _ => {
-                return None;
             }
         }
-    }
-    fn get_expectations<'e>(self: &Self) -> Vec<Expectation> {
-        let mut exp = Vec::new();
-
-        if let Some(parameters) = self.get_parameters() {
-            for s in parameters {
-                exp.push(Expectation::NumStaLit);
-            }
+        None => {
+            return Err(USAGE.to_string());
         }
-        return exp;
     }
 }
 
-fn next_lexeme<T: Iterator<Item = char>>(mut it: &mut Peekable<T>) -> Option<LexItem> {
-    let c = *(it.peek().unwrap());
-
-    //println!("lex {}", c);
-
-    match c {
-        '0'...'9' | '-' => {
-            it.next();
-            match lex_number(c, &mut it) {
-                Some(n) => {
-                    return Some(LexItem::Num(n));
+// splits the command line into settings and the sources to run
+fn parse_args(args: Vec<String>) -> Result<Options, String> {
+    let mut options = Options {
+        limits: EvalLimits::default(),
+        trace: TraceLevel::Off,
+        trace_format: TraceFormat::Text,
+        sources: Vec::new(),
+    };
+    let limits = &mut options.limits;
+    let sources = &mut options.sources;
+    let mut args_iter = args.into_iter();
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "-e" => match args_iter.next() {
+                Some(expr) => {
+                    sources.push(("-e".to_string(), expr));
                 }
                 None => {
-                    let a = lex_word(c, &mut it);
-                    return Some(LexItem::Word(a));
+                    return Err(USAGE.to_string());
                 }
+            },
+            "--max-steps" => {
+                limits.max_steps = parse_limit(&arg, args_iter.next())?;
+            }
+            "--max-depth" => {
+                limits.max_call_depth = parse_limit(&arg, args_iter.next())?;
+            }
+            "--max-items" => {
+                limits.max_stack_items = parse_limit(&arg, args_iter.next())?;
+            }
+            "--timeout" => {
+                let seconds: Option<f64> = parse_limit(&arg, args_iter.next())?;
+                limits.timeout = match seconds.map(Duration::try_from_secs_f64) {
+                    Some(Ok(d)) => Some(d),
+                    Some(Err(_)) => {
+                        return Err(format!("{}: invalid limit {}", arg, seconds.unwrap()));
+                    }
+                    None => None,
+                };
             }
+            "--trace" => {
+                options.trace = parse_value(args_iter.next())?;
+            }
+            "--trace-format" => {
+                options.trace_format = parse_value(args_iter.next())?;
+            }
+            filename => match fs::read_to_string(filename) {
+                Ok(text) => {
+                    sources.push((filename.to_string(), text));
+                }
+                Err(e) => {
+                    return Err(format!("{}: {}", filename, e));
+                }
+            },
         }
-        'A'...'Z' | 'a'...'z' | '+' => {
-            it.next();
-            let a = lex_word(c, &mut it);
-            return Some(LexItem::Word(a));
-        }
-
-        '$' => {
-            it.next();
-            let p = lex_parameter(c, &mut it);
-            return Some(LexItem::Parameter(p));
-        }
-        '#' => {
-            it.next();
-            return Some(LexItem::Word("#".to_string()));
-        }
-        ' ' | '\n' | '\t' => {
-            it.next();
-            return None;
-        }
-        '[' | '(' => {
-            it.next();
-            return Some(LexItem::OpenParen);
-        }
-        ']' | ')' => {
-            it.next();
-            return Some(LexItem::CloseParen);
-        }
-        '\'' => {
-            it.next();
-            return Some(LexItem::Quote);
-        }
-        _ => {
-            return None;
-        }
-    }
-}
-
-fn lex_parameter<T: Iterator<Item = char>>(inc: char, iter: &mut Peekable<T>) -> String {
-    let mut number = 0;
-    while let Some(Ok(digit)) = iter.peek().map(|c| c.to_string().parse::<i64>()) {
-        number = number * 10 + digit;
-        iter.next();
     }
-    return format!("{}", number);
+    return Ok(options);
 }
 
-fn lex_number<T: Iterator<Item = char>>(inc: char, iter: &mut Peekable<T>) -> Option<i64> {
-    let mut sign = 1;
-    let mut c = inc;
-    if inc == '-' {
-        sign = -1;
-        if *iter.peek().unwrap() == ' ' {
-            return None;
-        }
-        c = iter.next().unwrap();
-    }
-    let nparse = c.to_string().parse::<i64>();
-    match nparse {
-        Ok(n) => {
-            let mut number = n;
-            while let Some(Ok(digit)) = iter.peek().map(|c| c.to_string().parse::<i64>()) {
-                number = number * 10 + digit;
-                iter.next();
+// :trace shows the level, :trace LEVEL or :trace FORMAT changes it
+fn trace_command(interpreter: &mut Interpreter, args: &str) -> () {
+    let arg = args.trim();
+    if arg.len() == 0 {
+        println!("trace level: {}", interpreter.trace_level());
+    } else if let Ok(level) = arg.parse::<TraceLevel>() {
+        interpreter.set_trace_level(level);
+    } else {
+        match arg.parse::<TraceFormat>() {
+            Ok(format) => {
+                interpreter.set_trace_format(format);
+            }
+            Err(_) => {
+                eprintln!("usage: :trace [off|summary|steps|full|text|json]");
             }
-            //println!("get_number {}", number * sign);
-            return Some(number * sign);
-        }
-        Err(_) => {
-            //println!("end of number");
-            return None;
         }
     }
 }
 
-fn lex_word<T: Iterator<Item = char>>(c: char, iter: &mut Peekable<T>) -> String {
-    let mut word = c.to_string();
-    //println!("word c: {}", c);
-    while let Some(&letter) = iter.peek() {
-        //println!("word peek: {}", letter);
-        if letter == ' ' || letter == ']' {
-            break;
-        }
+const HELP: &str = "commands:
+  :words              list the dictionary, defined words marked with *
+  :see WORD           show what a word expects and what it was defined as
+  :forget WORD        remove a word from the dictionary
+  :reset              go back to only the builtins
+  :load FILE          evaluate a file
+  :save FILE          write the defined words to a file
+  :save-image [FILE]  keep the defined words in an image, loaded again at startup
+  :load-image [FILE]  add the words of an image to the dictionary
+  :trace [LEVEL]      show or set tracing: off, summary, steps, full, text or json
+  :debug EXPR         step through an expression
+  :help               this list";
 
-        word.push(letter);
-        iter.next();
+fn show_words(interpreter: &Interpreter) -> () {
+    for name in interpreter.words() {
+        let defined = if interpreter.definition(&name).is_some() {
+            "*"
+        } else {
+            " "
+        };
+        let expectations = interpreter.word_expectations(&name).unwrap_or_default();
+        println!(
+            "{} {} ({}) {}",
+            defined,
+            name,
+            expectations.len(),
+            describe(&expectations)
+        );
     }
-    //println!("get_word {}", word);
-    word
 }
 
-fn print_lexeme(token: &LexItem) -> String {
-    let mut value = "".to_string();
-    match token {
-        LexItem::Word(w) => {
-            value = w.to_string();
-        }
-        LexItem::Num(n) => {
-            value = n.to_string();
+fn see_word(interpreter: &Interpreter, name: &str) -> () {
+    let expectations = match interpreter.word_expectations(name) {
+        Some(e) => e,
+        None => {
+            eprintln!("unknown word: {}", name);
+            return;
         }
-        LexItem::Literal(l) => {
-            value = l.to_string();
-        }
-        LexItem::OpenParen => {
-            value = "[".to_string();
-        }
-        LexItem::CloseParen => {
-            value = "]".to_string();
-        } //LexItem::WhiteSpace => {descriptor = "WhiteSpace"; value = " ".to_string();}
-        LexItem::Stack(s) => {
-            value = format_lexstack(s);
+    };
+    println!("expects: {}", describe(&expectations));
+    match interpreter.definition(name) {
+        Some(value) => {
+            println!("{} {}", name, format_source(value));
         }
-        LexItem::Parameter(p) => {
-            value = format!("${}", p);
+        None => {
+            println!("{} is a builtin", name);
         }
-        _ => (),
     }
-    return format!("{} ", value);
 }
 
-fn parse_stacks<'i>(
-    lex_input: &mut Vec<LexItem>,
-    parsed_input: &'i mut Vec<LexItem>,
-) -> &'i mut Vec<LexItem> {
-    let mut quoted = false;
-    while let Some(itop) = lex_input.pop() {
-        match itop {
-            LexItem::OpenParen => {
-                //println!("openbracket found");
-                let mut newstack = &mut Vec::new();
-                newstack = parse_stacks(lex_input, newstack);
-                if quoted {
-                    parsed_input.insert(0, LexItem::Stack(newstack.to_vec()));
-                    quoted = false;
-                } else {
-                    parsed_input.insert(0, LexItem::Lambda(newstack.to_vec()));
-                }
-            }
-            LexItem::CloseParen => {
-                //println!("closedbracket found");
-
-                return parsed_input;
-            }
-            LexItem::Quote => {
-                quoted = true;
-                println!("Parse: found quote");
-            }
-
-            LexItem::Num(n) => {
-                let mut lexeme = LexItem::Num(n);
-                if quoted {
-                    let lexeme = LexItem::Literal(n.to_string());
-                    quoted = false;
-                }
-                //println!("lexeme found: {}", print_lexeme(&itop));
-                parsed_input.insert(0, lexeme);
-                let p = parse_stacks(lex_input, parsed_input);
-
-                return p;
-            }
-            LexItem::Word(w) => {
-                if quoted {
-                    let lexeme = LexItem::Literal(w.to_string());
-                    parsed_input.insert(0, lexeme);
-                    quoted = false;
-                } else {
-                    //println!("lexeme found: {}", print_lexeme(&itop));
-                    parsed_input.insert(0, LexItem::Word(w));
-                }
-                let p = parse_stacks(lex_input, parsed_input);
-
-                return p;
-            }
-            _ => {
-                //println!("lexeme found: {}", print_lexeme(&itop));
-                parsed_input.insert(0, itop);
-                let p = parse_stacks(lex_input, parsed_input);
-
-                return p;
+fn load_file(interpreter: &mut Interpreter, filename: &str) -> () {
+    let text = match fs::read_to_string(filename) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}: {}", filename, e);
+            return;
+        }
+    };
+    match interpreter.eval_str(&text) {
+        Ok(values) => {
+            println!("ostack: {}", format_values(&values));
+        }
+        Err(e) => {
+            report_error(interpreter, e);
+            if interpreter.is_suspended() {
+                interpreter.abort();
             }
         }
     }
-    return parsed_input;
-}
-
-#[derive(Debug, Clone)]
-struct Parameter {
-    name: String,
-    value: LexItem,
 }
 
-struct Call {
-    name: String,
-    action: fn(call: &mut Call) -> (),
-    arity: usize,
-    arguments: Vec<Parameter>,
-    expectations: Vec<Expectation>,
-    results: Vec<LexItem>,
-    substitution: Option<LexItem>,
-}
-impl fmt::Debug for Call {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{}, args:{}, result:{}",
-            self.name,
-            format_parstack(&self.arguments),
-            format_lexstack(&self.results)
-        )
+fn save_file(interpreter: &Interpreter, filename: &str) -> () {
+    if let Err(e) = fs::write(filename, interpreter.definitions_source()) {
+        eprintln!("{}: {}", filename, e);
     }
 }
-struct CallStack {
-    stack: Vec<Call>,
-    words: HashMap<String, Word>,
-}
-impl CallStack {
-    fn pushSearchWord(self: &mut Self, word: String) -> () {
-        match self.words.get(&word) {
-            Some(w) => {
-                self.stack.push(make_call(w));
-                println!("pushed Call: {}, Stack: {:?}", w.name, self.stack);
-            }
-            None => {
-                let defword = self.words.get(&"define".to_string()).unwrap();
-
-                let mut c = make_call(defword);
 
-                self.stack.push(c);
-                println!(
-                    "pushed Define {}, Stack: {:?}",
-                    word.to_string(),
-                    self.stack
-                );
-
-                self.pushLexItem(&mut LexItem::Word(word));
-            }
-        }
-    }
-    fn pushWordCall(self: &mut Self, word: &mut Word) -> () {
-        //println!("pushWordCall {:?}", word);
-        self.stack.push(make_call(word));
+// the image loaded at startup and used when :save-image or :load-image get no file
+const IMAGE: &str = "staplr_image.stp";
 
-        return ();
+fn save_image(interpreter: &Interpreter, filename: &str) -> () {
+    if let Err(e) = fs::write(filename, interpreter.image()) {
+        eprintln!("{}: {}", filename, e);
     }
+}
 
-    fn len(self: &Self) -> usize {
-        return self.stack.len();
-    }
-
-    fn getExpectation(self: &Self) -> Option<&Expectation> {
-        let top_call: &Call;
-        match self.stack.last() {
-            Some(call) => {
-                top_call = call;
-            }
-            None => {
-                return None;
-            }
-        };
-
-        return top_call.expectations.last();
-    }
-    fn create_builtin_words(self: &mut Self) -> () {
-        let addword = Word {
-            name: "+".to_string(),
-            arity: 2,
-            action: action_add,
-            substitution: None,
-            expectations: vec![Expectation::Num, Expectation::Num],
-        };
-        self.words.insert("+".to_string(), addword);
-
-        let addword = Word {
-            name: "-".to_string(),
-            arity: 2,
-            action: action_subtract,
-            substitution: None,
-            expectations: vec![Expectation::Num, Expectation::Num],
-        };
-        self.words.insert("-".to_string(), addword);
-
-        let string2 = String::from("if");
-        let ifword = Word {
-            name: "if".to_string(),
-            arity: 3,
-            action: action_if,
-            substitution: None,
-            expectations: vec![Expectation::Any, Expectation::Any, Expectation::Num],
-        };
-        self.words.insert(string2, ifword);
-
-        let defword = Word {
-            name: "define".to_string(),
-            arity: 1,
-            action: action_none, // change this to an option?
-            substitution: None,
-            expectations: vec![Expectation::Any, Expectation::Word],
-        };
-        self.words.insert("define".to_string(), defword);
-
-        let defextract = Word {
-            name: "#".to_string(),
-            arity: 2,
-            action: action_extract, // change this to an option?
-            substitution: None,
-            expectations: vec![Expectation::Stack, Expectation::Num],
-        };
-        self.words.insert("#".to_string(), defextract);
-
-        let deftake = Word {
-            name: "take".to_string(),
-            arity: 1,
-            action: action_take,
-            substitution: None,
-            expectations: vec![Expectation::Num],
-        };
-        self.words.insert("take".to_string(), deftake);
-
-        let defprint = Word {
-            name: "print".to_string(),
-            arity: 1,
-            action: action_print,
-            substitution: None,
-            expectations: vec![Expectation::NumStaLit],
-        };
-        self.words.insert("print".to_string(), defprint);
-    }
-
-    fn pushLexItem<'l>(self: &mut Self, lexeme: &'l mut LexItem) -> Option<&'l mut LexItem> {
-        if let Some(top_call) = self.stack.last_mut() {
-            let e = &mut top_call.expectations;
-            match e.last() {
-                Some(&top_expectation) => {
-                    //let d = check_expectation(top_expectation, lexeme.clone());
-
-                    //let dataitem = lexeme;
-                    let mut expectation_match = true;
-                    match (&top_expectation, &lexeme) {
-                        (Expectation::Num, LexItem::Num(_))
-                        | (Expectation::NumStaLit, LexItem::Num(_)) => (),
-                        (Expectation::Word, LexItem::Word(_)) => (),
-                        (Expectation::Parameter, LexItem::Parameter(_)) => (),
-                        (Expectation::Stack, LexItem::Stack(_))
-                        | (Expectation::NumStaLit, LexItem::Stack(_)) => (),
-                        (Expectation::Stack, LexItem::Literal(_))
-                        | (Expectation::NumStaLit, LexItem::Literal(_)) => (),
-                        (Expectation::Lambda, LexItem::Lambda(_)) => (),
-                        (Expectation::Any, _) => (),
-                        _ => {
-                            expectation_match = false;
-                        }
-                    };
-
-                    if expectation_match {
-                        e.pop();
-                        let parname = format!("{}", top_call.arguments.len() + 1);
-                        top_call.arguments.insert(
-                            0,
-                            Parameter {
-                                name: parname,
-                                value: lexeme.to_owned(),
-                            },
-                        );
-                        return None;
-                    }
-                    //could put apply here
-                }
-                None => {}
-            }
+fn load_image(interpreter: &mut Interpreter, filename: &str) -> () {
+    let result = fs::read_to_string(filename)
+        .map_err(|e| e.to_string())
+        .and_then(|image| interpreter.load_image(&image));
+    match result {
+        Ok(count) => {
+            println!("loaded {} words from {}", count, filename);
         }
-        /* println!(
-            "pushed item: {}, Stack: {:?}",
-            print_lexeme(top_call.arguments.last().unwrap()),
-            self.stack
-        ); */
-        return Some(lexeme);
-    }
-
-    fn wantsData(self: &Self) -> bool {
-        match self.stack.last() {
-            Some(call) => {
-                match call.expectations.last() {
-                    Some(e) => {
-                        print!("expects: {}\n", format_expstack(&call.expectations));
-                    }
-                    None => {
-                        print!("expects: None\n");
-                    }
-                }
-                return call.expectations.len() > 0;
-            }
-            None => {
-                return false;
-            }
-        };
-    }
-    fn top_apply(self: &mut Self, result: &mut Vec<LexItem>) -> bool {
-        match self.stack.last_mut() {
-            Some(top_call) => {
-                println!(
-                    "apply {} with parameters : {}",
-                    top_call.name,
-                    format_parstack(&top_call.arguments)
-                );
-                match top_call.name.as_str() {
-                    "define" => {
-                        //action_define(&mut self.words, top_call);
-                        let first = top_call.arguments.pop().unwrap().value;
-                        match first {
-                            LexItem::Word(w) => {
-                                let value = top_call.arguments.pop().unwrap().value;
-                                let mut newword = Word::make_word(
-                                    w.to_string(),
-                                    0,
-                                    action_substitution,
-                                    Some(value),
-                                );
-                                let n2 = newword.setup_expectations();
-                                self.words.insert(w.to_string(), newword);
-                                top_call.expectations.pop();
-                            }
-                            _ => {
-                                println!("expected word");
-                            }
-                        }
-                    }
-                    "take" => {
-                        println!("top_apply in take");
-                        let new_arity = top_call.arguments.pop().unwrap().value;
-                        top_call.arity = new_arity.get_Num().unwrap() as usize;
-                        top_call.expectations.pop();
-                        for i in 0..top_call.arity {
-                            top_call.expectations.push(Expectation::NumStaLit);
-                        }
-                        top_call.name = "take_ready".to_string();
-                        println!("reset take arity");
-                        return true;
-                    }
-
-                    _ => {
-                        (top_call.action)(top_call);
-                    }
-                }
-
-                result.append(&mut top_call.results);
-                //result.insert(0, LexItem::Stack(top_call.results.clone()));
-                println!("call result {}", format_lexstack(result));
-
-                self.stack.pop();
-                return true;
-            }
-            _ => {
-                return false;
-            }
+        Err(e) => {
+            eprintln!("{}: {}", filename, e);
         }
     }
 }
 
-//#[derive(Debug)]
-struct Word {
-    name: String,
-    arity: usize,
-    action: fn(call: &mut Call) -> (),
-    substitution: Option<LexItem>,
-    expectations: Vec<Expectation>,
-}
-impl Word {
-    fn make_word<'w>(
-        name: String,
-        arity: usize,
-        action: fn(call: &mut Call) -> (),
-        //expectations: &'w mut Vec<Expectation>,
-        substitution: Option<LexItem>,
-    ) -> Word {
-        let mut expectations: Vec<Expectation> = Vec::new();
-        let sub = substitution.clone();
-        match substitution {
-            Some(s) => {
-                expectations = s.get_expectations();
-                println!(
-                    "made word {},arity {}, {}",
-                    name,
-                    expectations.len(),
-                    print_lexeme(&s)
-                );
-            }
-            None => (),
-        }
-        let mut word = Word {
-            name: name,
-            arity: expectations.len(),
-            expectations: expectations,
-            action: action,
-            substitution: sub,
-        };
-
-        return word;
+// runs a :command, false when the line is not one and should be evaluated
+fn command(reader: &mut Editor<ReplHelper>, interpreter: &mut Interpreter, line: &str) -> bool {
+    let line = line.trim();
+    if !line.starts_with(':') {
+        return false;
     }
-    fn setup_expectations(self: &mut Self) -> &mut Self {
-        let sub = &mut self.substitution;
-        let mut e = &mut self.expectations;
-        match sub {
-            Some(s) => {
-                let e2 = s.get_expectations();
-                self.expectations = e2;
-            }
-            None => {}
+    let (name, args) = match line.find(char::is_whitespace) {
+        Some(i) => (&line[..i], line[i..].trim()),
+        None => (line, ""),
+    };
+    match (name, args) {
+        (":words", "") => {
+            show_words(interpreter);
         }
-        return self;
-    }
-}
-fn action_none(call: &mut Call) -> () {
-    return ();
-}
-fn action_print(call: &mut Call) -> () {
-    let item = call.arguments.pop().unwrap().value;
-    println!("printing:{}", print_lexeme(&item));
-}
-fn action_take(call: &mut Call) -> () {
-    let mut newstack = Vec::new();
-    for a in 0..call.arguments.len() {
-        newstack.insert(0, call.arguments.pop().unwrap().value);
-    }
-
-    call.results.push(LexItem::Stack(newstack.to_owned()));
-}
-fn action_extract(call: &mut Call) {
-    let index = call.arguments.pop().unwrap().value.get_Num().unwrap() as i64;
-    let s = call.arguments.pop().unwrap().value;
-    if let LexItem::Stack(s) = s {
-        if s.len() > 0 {
-            if index > 0 && index <= s.len() as i64 {
-                call.results
-                    .push(s.get((s.len() as i64 - index) as usize).unwrap().clone());
-            } else if index < 0 && index >= -(s.len() as i64) {
-                call.results
-                    .push(s.get((-(index + 1)) as usize).unwrap().clone());
-            }
+        (":see", word) if word.len() > 0 => {
+            see_word(interpreter, word);
         }
-    }
-}
-fn action_add(call: &mut Call) -> () {
-    println!("action_add arguments {:?}", call.arguments);
-    let a = call.arguments.pop().unwrap().value.get_Num().unwrap();
-    let b = call.arguments.pop().unwrap().value.get_Num().unwrap();
-    call.results.push(LexItem::Num(a + b));
-    //println!("action_add {} + {}", a, b);
-}
-fn action_subtract(call: &mut Call) -> () {
-    //println!("action_add arguments {:?}", call.arguments);
-    let a = call.arguments.pop().unwrap().value.get_Num().unwrap();
-    let b = call.arguments.pop().unwrap().value.get_Num().unwrap();
-    call.results.push(LexItem::Num(a - b));
-    //println!("action_add {} + {}", a, b);
-}
-fn action_parameter(call: &mut Call) -> Option<LexItem> {
-    if let Some(l) = call.arguments.pop() {
-        if let LexItem::Parameter(p) = l.value {
-            for a in call.arguments.iter() {
-                let mut value;
-                if p == a.name {
-                    value = a.value.clone();
-                    //println!("reduced match p: {} to {:?}", *p, a.value);
-                    return Some(value); //should only happen once ||X/  \X||
-                }
+        (":forget", word) if word.len() > 0 => {
+            if !interpreter.forget(word) {
+                eprintln!("unknown word: {}", word);
             }
         }
-    }
-    return None;
-}
-fn check_conditional(l: LexItem) -> bool {
-    match l {
-        LexItem::Num(n) => {
-            if n == 0 {
-                return false;
-            } else {
-                return true;
-            }
+        (":reset", "") => {
+            interpreter.reset_dictionary();
         }
-        _ => return false,
-    }
-}
-fn action_if(call: &mut Call) -> () {
-    //println!("action_if arguments {:?}", call.arguments);
-    let conditional = call.arguments.pop().unwrap().value;
-    let if_clause = call.arguments.pop().unwrap().value;
-
-    let else_clause = call.arguments.pop().unwrap().value;
-
-    if check_conditional(conditional) {
-        call.results.push(if_clause);
-    } else {
-        call.results.push(else_clause);
-    }
-}
-
-fn parameter_reduce<'a>(a: &'a Parameter, l: &'a LexItem) -> Option<&'a LexItem> {
-    match l {
-        LexItem::Parameter(p) => {
-            if a.name == *p {
-                return Some(&a.value);
-            } else {
-                return None;
-            }
+        (":load", filename) if filename.len() > 0 => {
+            load_file(interpreter, filename);
         }
-        LexItem::Stack(s) => {
-            return None;
+        (":save", filename) if filename.len() > 0 => {
+            save_file(interpreter, filename);
         }
-        _ => {
-            return None;
+        (":save-image", "") => {
+            save_image(interpreter, IMAGE);
         }
-    }
-}
-
-fn action_substitution(call: &mut Call) -> () {
-    let sub = call.substitution.take();
-    if sub.is_none() {
-        return ();
-    }
-
-    //beta reduction
-    let mut betasub = Vec::new();
-    if call.arity > 0 {
-        match sub.unwrap() {
-            LexItem::Lambda(s) => {
-                let mut curstack = Vec::new();
-                curstack.push(s);
-
-                while let Some(current) = curstack.pop() {
-                    for mut value in current {
-                        for a in call.arguments.iter() {
-                            if let Some(p) = parameter_reduce(a, &value) {
-                                //if let LexItem::Stack(s) = p {
-                                // curstack.push(s.to_vec());
-                                //} else {
-                                value = p.clone();
-                                //}
-                            }
-                        }
-                        betasub.push(value);
-                    }
-                }
-            }
-            _ => (),
+        (":save-image", filename) => {
+            save_image(interpreter, filename);
         }
-        call.results = betasub;
-    } else {
-        match sub.unwrap() {
-            LexItem::Lambda(s) => {
-                call.results = s;
-            }
-            mut s => {
-                call.results = vec![s];
-            }
+        (":load-image", "") => {
+            load_image(interpreter, IMAGE);
+        }
+        (":load-image", filename) => {
+            load_image(interpreter, filename);
+        }
+        (":trace", args) => {
+            trace_command(interpreter, args);
+        }
+        (":debug", expr) => {
+            debug_session(reader, interpreter, expr);
+        }
+        _ => {
+            println!("{}", HELP);
         }
     }
-
-    println!("sub: {:?}", call);
-    return ();
-}
-
-fn make_call(word: &Word) -> Call {
-    let sub = word.substitution.clone();
-    let a = Call {
-        name: word.name.to_string(),
-        action: word.action,
-        arity: word.arity,
-        arguments: Vec::new(),
-        expectations: word.expectations.to_vec(),
-        results: Vec::new(),
-        substitution: sub,
-    };
-
-    return a;
+    interpreter.clear_output();
+    return true;
 }
 
-struct Program {
-    name: String,
-    filename: String,
-    istack: Vec<LexItem>,
-    cstack: CallStack,
-    ostack: Vec<LexItem>,
-}
+const DEBUG_HELP: &str =
+    "s(tep), n(ext), f(inish), c(ontinue), b(reak) [WORD], d(elete) WORD, q(uit)";
 
-fn eval<'o>(program: &'o mut Program) -> &'o mut Program {
-    let mut istack = &mut program.istack;
-    let mut cstack = &mut program.cstack;
-    let mut ostack = &mut program.ostack;
-    //add '+' builtin
-    let mut loopcount = 0;
-    println!("cstack: {:?}", cstack.stack);
-
-    println!("istack: {:?}", format_lexstack(&istack));
+// steps through expr, an empty line repeats a step
+fn debug_session(reader: &mut Editor<ReplHelper>, interpreter: &mut Interpreter, expr: &str) -> () {
+    if let Err(e) = interpreter.debug_start(expr) {
+        report_error(interpreter, e);
+        return;
+    }
+    println!("{}", DEBUG_HELP);
     loop {
-        loopcount = loopcount + 1;
-        if loopcount > 100 {
-            println!("exceeded maximum loops");
-            break;
+        println!("{}", interpreter.debug_view());
+        let line = match reader.readline("debug> ") {
+            Ok(line) => line,
+            Err(_) => {
+                interpreter.abort();
+                return;
+            }
         };
-        while !cstack.wantsData() {
-            if cstack.len() > 0 {
-                let result = cstack.top_apply(&mut istack);
-                println!("istack: {}", format_lexstack(&istack));
-            } else {
-                break;
-            };
-        }
-        println!("cstack: {:?}", cstack.stack);
-        println!("istack: {:?}", format_lexstack(&istack));
-        if istack.len() == 0 {
-            break;
-        }
-        match istack.pop().unwrap() {
-            LexItem::Word(w) => {
-                let mut pw = &mut LexItem::Word(w);
-                let lexreturn = cstack.pushLexItem(&mut pw);
-                match lexreturn {
-                    Some(LexItem::Word(w)) => {
-                        cstack.pushSearchWord(w.to_string());
-                    }
-                    _ => {}
+        let mut words = line.split_whitespace();
+        let command = match words.next() {
+            None | Some("s") | Some("step") => DebugCommand::Step,
+            Some("n") | Some("next") => DebugCommand::Next,
+            Some("f") | Some("finish") => DebugCommand::Finish,
+            Some("c") | Some("continue") => DebugCommand::Continue,
+            Some("b") | Some("break") => {
+                match words.next() {
+                    Some(word) => interpreter.add_breakpoint(word),
+                    None => println!("breakpoints: {}", interpreter.breakpoints().join(" ")),
                 }
+                continue;
             }
-
-            LexItem::Num(n) => {
-                if cstack.pushLexItem(&mut LexItem::Num(n)).is_none() {
-                    println!("pushed expected item: {}", print_lexeme(&LexItem::Num(n)));
-                } else {
-                    let c = cstack.stack.last_mut();
-                    if let Some(top_call) = c {
-                        top_call.results.push(LexItem::Num(n));
-                    } else {
-                        println!("output item: {}", print_lexeme(&LexItem::Num(n)));
-                        ostack.push(LexItem::Num(n));
+            Some("d") | Some("delete") => {
+                if let Some(word) = words.next() {
+                    if !interpreter.remove_breakpoint(word) {
+                        println!("no breakpoint on {}", word);
                     }
                 }
+                continue;
             }
-            LexItem::Lambda(mut s) => {
-                let stack = &mut LexItem::Lambda(s);
-                let lexreturn = cstack.pushLexItem(stack);
-                match lexreturn {
-                    Some(l) => {
-                        let arity = l.get_arity();
-                        let mut w = Word::make_word(
-                            "lambda".to_string(),
-                            arity,
-                            action_substitution,
-                            Some(l.clone()),
-                        );
-                        w.setup_expectations();
-                        cstack.pushWordCall(&mut w);
-                        //istack.append(l);
-                    }
-                    _ => {}
-                }
+            Some("q") | Some("quit") => {
+                interpreter.abort();
+                return;
             }
-            LexItem::Stack(mut s) => {
-                if let Some(l) = cstack.pushLexItem(&mut LexItem::Stack(s)) {
-                    //println!("output item: {}", format_lexstack(&s));
-                    ostack.push(l.clone());
-                }
+            Some(_) => {
+                println!("{}", DEBUG_HELP);
+                continue;
             }
-            LexItem::Parameter(p) => {
-                let c = cstack.stack.last_mut();
-                if let Some(top_call) = c {
-                    match action_parameter(top_call) {
-                        Some(lexeme) => {
-                            istack.push(lexeme);
-                        }
-                        None => (),
-                    }
+        };
+        match interpreter.debug_run(command) {
+            Ok(DebugStop::Paused) => {}
+            Ok(DebugStop::Breakpoint(word)) => {
+                println!("breakpoint: {}", word);
+            }
+            Ok(DebugStop::Finished(values)) => {
+                println!("ostack: {}", format_values(&values));
+                return;
+            }
+            Err(e) => {
+                report_error(interpreter, e);
+                if interpreter.is_suspended() {
+                    interpreter.abort();
                 }
+                return;
             }
-            _ => (),
         }
     }
+}
 
-    println!("cstack: {:?}", cstack.stack);
-    return program;
+fn evaluate(reader: &mut Editor<ReplHelper>, interpreter: &mut Interpreter, line: &str) -> () {
+    let mut result = interpreter.eval_str(line);
+    // a limit reached between steps leaves the evaluation suspended, offer to carry on with it
+    while result.is_err() && interpreter.is_suspended() {
+        report_error(interpreter, result.unwrap_err());
+        match reader.readline("resume? [y/N] ") {
+            Ok(ref answer) if answer.trim() == "y" => {
+                result = interpreter.resume();
+            }
+            _ => {
+                interpreter.abort();
+                result = Ok(Vec::new());
+            }
+        }
+    }
+    match result {
+        Ok(values) => {
+            println!("ostack: {}", format_values(&values));
+        }
+        Err(e) => {
+            report_error(interpreter, e);
+        }
+    }
+    interpreter.clear_output();
 }
-fn parse_input<'i>(input: String, lexstack: &'i mut Vec<LexItem>) -> &'i mut Vec<LexItem> {
-    if input.len() > 1 {
-        println!("The first argument is {}", input);
 
-        //lex all of the input
-        let mut it = input.chars().peekable();
-        while it.peek() != None {
-            match next_lexeme(&mut it) {
-                Some(lexeme) => {
-                    lexstack.insert(0, lexeme);
-                }
-                _ => (),
+// the sources share one interpreter, so words defined in one are there for the next
+fn run_batch(options: Options) -> i32 {
+    let mut interpreter = Interpreter::new("batch");
+    options.configure(&mut interpreter);
+    for (filename, text) in options.sources.iter() {
+        interpreter.set_name(filename);
+        match interpreter.eval_str(text) {
+            Ok(values) => {
+                println!("{}", format_values(&values));
+            }
+            Err(e) => {
+                report_error(&interpreter, e);
+                return 1;
             }
         }
+        interpreter.clear_output();
     }
-    return lexstack;
+    return 0;
 }
+
 fn main() {
-    let mut reader = Editor::<()>::new();
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_args(args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{}", message);
+            process::exit(2);
+        }
+    };
+    if options.sources.len() > 0 {
+        process::exit(run_batch(options));
+    }
+
+    let mut interpreter = Interpreter::new("REPL");
+    options.configure(&mut interpreter);
+    if Path::new(IMAGE).exists() {
+        load_image(&mut interpreter, IMAGE);
+    }
+    let mut reader = Editor::<ReplHelper>::new();
+    reader.set_helper(Some(ReplHelper::new(&interpreter)));
     if let Err(_) = reader.load_history("staplr_history.txt") {
         println!("No previous history.");
     }
-    // Program p;
-    let mut cstack = CallStack {
-        stack: Vec::new(),
-        words: HashMap::new(),
-    };
-    cstack.create_builtin_words();
-
-    let ostack: Vec<LexItem> = Vec::new();
-    let istack: Vec<LexItem> = Vec::new();
-    let mut program = Program {
-        name: "REPL".to_string(),
-        cstack: cstack,
-        istack: istack,
-        ostack: ostack,
-        filename: "".to_string(),
-    };
 
     loop {
         let readline = reader.readline("STAPLr> ");
 
-        //let args: Vec<_> = env::args().collect();
-
         match readline {
+            // interrupting input that runs over several lines drops it
+            Err(ReadlineError::Interrupted)
+                if reader.helper().map_or(false, |h| h.take_continued()) => {}
             Ok(line) => {
-                let mut lexstack = &mut Vec::new();
-                reader.add_history_entry(line.as_ref());
-                parse_input(line.to_string(), &mut lexstack);
-                program.istack = parse_stacks(lexstack, &mut program.istack).to_owned();
-                eval(&mut program);
-                //program.istack.clear();
-                println!("input was: {}", line.to_string());
-                println!("ostack: {}", format_lexstack(&program.ostack));
-                program.ostack.clear();
+                reader.add_history_entry(line.as_str());
+                let handled = command(&mut reader, &mut interpreter, &line);
+                if !handled {
+                    evaluate(&mut reader, &mut interpreter, &line);
+                }
+                if let Some(helper) = reader.helper_mut() {
+                    helper.refresh(&interpreter);
+                }
             }
             _ => {
                 break;
@@ -1025,5 +480,4 @@ fn main() {
         }
     }
     reader.save_history("staplr_history.txt").unwrap();
-    // println!("Hello, world! {}", match words.stack.get("define") { Some(fun) => fun, None=> "none" });
 }
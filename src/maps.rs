@@ -0,0 +1,215 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::arithmetic::push_truth;
+use crate::error::StaplError;
+use crate::eval::{Call, CallStack, Env};
+use crate::lex::{Expectation, LexItem};
+
+impl CallStack {
+    pub(crate) fn create_map_words(self: &mut Self) -> () {
+        self.add_builtin(
+            "get",
+            vec![Expectation::NumStaLit, Expectation::Map],
+            Rc::new(action_get),
+        );
+        self.add_builtin(
+            "put",
+            vec![Expectation::Value, Expectation::NumStaLit, Expectation::Map],
+            Rc::new(action_put),
+        );
+        self.add_builtin(
+            "remove",
+            vec![Expectation::NumStaLit, Expectation::Map],
+            Rc::new(action_remove),
+        );
+        self.add_builtin("keys", vec![Expectation::Map], Rc::new(action_keys));
+        self.add_builtin("values", vec![Expectation::Map], Rc::new(action_values));
+        self.add_builtin(
+            "has",
+            vec![Expectation::NumStaLit, Expectation::Map],
+            Rc::new(action_has),
+        );
+    }
+}
+
+// key and value pairs in the order their keys were first put. two maps are
+// equal when they hold the same pairs, whatever the order
+#[derive(Clone, Default)]
+pub struct Entries {
+    pub(crate) pairs: Vec<(LexItem, LexItem)>,
+}
+
+impl Entries {
+    pub fn get(self: &Self, key: &LexItem) -> Option<&LexItem> {
+        return self.pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v);
+    }
+    pub fn len(self: &Self) -> usize {
+        return self.pairs.len();
+    }
+    pub fn iter(self: &Self) -> std::slice::Iter<'_, (LexItem, LexItem)> {
+        return self.pairs.iter();
+    }
+    // a key that is already there keeps its place
+    pub fn put(self: &mut Self, key: LexItem, value: LexItem) -> () {
+        match self.pairs.iter_mut().find(|(k, _)| *k == key) {
+            Some(pair) => {
+                pair.1 = value;
+            }
+            None => {
+                self.pairs.push((key, value));
+            }
+        }
+    }
+    pub(crate) fn remove(self: &mut Self, key: &LexItem) -> Option<LexItem> {
+        let i = self.pairs.iter().position(|(k, _)| k == key)?;
+        return Some(self.pairs.remove(i).1);
+    }
+}
+
+impl PartialEq for Entries {
+    fn eq(&self, other: &Entries) -> bool {
+        return self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v));
+    }
+}
+
+// maps are only ordered as far as equality goes
+impl PartialOrd for Entries {
+    fn partial_cmp(&self, other: &Entries) -> Option<Ordering> {
+        if self == other {
+            return Some(Ordering::Equal);
+        }
+        return None;
+    }
+}
+
+impl fmt::Debug for Entries {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.iter().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+
+// numbers, strings and literals can be keys
+pub(crate) fn is_key(key: &LexItem) -> bool {
+    match key {
+        LexItem::Num(..) | LexItem::Str(..) | LexItem::Literal(..) => true,
+        _ => false,
+    }
+}
+
+// keys and values stored like a body, the first key last
+pub(crate) fn map_items(entries: &Entries) -> Vec<LexItem> {
+    let mut items = Vec::new();
+    for (key, value) in entries.iter().rev() {
+        items.push(value.clone());
+        items.push(key.clone());
+    }
+    return items;
+}
+
+// pairs up the items of a map literal, stored last item first. a later entry
+// replaces an earlier one with the same key, a key without a value is returned
+pub(crate) fn map_entries(mut items: Vec<LexItem>) -> Result<Entries, LexItem> {
+    let mut entries = Entries::default();
+    while let Some(key) = items.pop() {
+        match items.pop() {
+            Some(value) => {
+                entries.put(key, value);
+            }
+            None => {
+                return Err(key);
+            }
+        }
+    }
+    return Ok(entries);
+}
+
+// the first key of a map that can't be one, checked when the map is evaluated
+pub(crate) fn check_keys(entries: &Entries) -> Option<LexItem> {
+    for (key, _) in entries.iter() {
+        if !is_key(key) {
+            return Some(key.clone());
+        }
+    }
+    return None;
+}
+
+fn pop_map(call: &mut Call) -> Result<Entries, StaplError> {
+    let value = call.pop_argument()?;
+    match value {
+        LexItem::Map(m, _) => {
+            return Ok(m);
+        }
+        _ => {
+            return Err(call.type_mismatch(Expectation::Map, value));
+        }
+    }
+}
+fn pop_key(call: &mut Call) -> Result<LexItem, StaplError> {
+    let key = call.pop_argument()?;
+    if !is_key(&key) {
+        return Err(call.type_mismatch(Expectation::Literal, key));
+    }
+    return Ok(key);
+}
+fn push_map(call: &mut Call, entries: Entries) -> () {
+    call.push_result(LexItem::Map(entries, call.span().clone()));
+}
+
+// get map key
+pub(crate) fn action_get(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut m = pop_map(call)?;
+    let key = pop_key(call)?;
+    match m.remove(&key) {
+        Some(value) => {
+            call.push_result(value);
+        }
+        None => {
+            return Err(StaplError::KeyNotFound {
+                word: call.name.to_string(),
+                key: key,
+                span: call.span().clone(),
+            });
+        }
+    }
+    return Ok(());
+}
+// put map key value: the map with the key set to the value
+pub(crate) fn action_put(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut m = pop_map(call)?;
+    let key = pop_key(call)?;
+    let value = call.pop_argument()?;
+    m.put(key, value);
+    push_map(call, m);
+    return Ok(());
+}
+// remove map key: the map without the key, which need not be there
+pub(crate) fn action_remove(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let mut m = pop_map(call)?;
+    let key = pop_key(call)?;
+    m.remove(&key);
+    push_map(call, m);
+    return Ok(());
+}
+// keys and values come as stacks in the order the keys were put
+pub(crate) fn action_keys(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let m = pop_map(call)?;
+    let keys: Vec<LexItem> = m.pairs.into_iter().rev().map(|(k, _)| k).collect();
+    call.push_result(LexItem::Stack(keys, call.span().clone()));
+    return Ok(());
+}
+pub(crate) fn action_values(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let m = pop_map(call)?;
+    let values: Vec<LexItem> = m.pairs.into_iter().rev().map(|(_, v)| v).collect();
+    call.push_result(LexItem::Stack(values, call.span().clone()));
+    return Ok(());
+}
+pub(crate) fn action_has(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let m = pop_map(call)?;
+    let key = pop_key(call)?;
+    push_truth(call, m.get(&key).is_some());
+    return Ok(());
+}
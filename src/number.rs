@@ -0,0 +1,258 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+// the numeric tower: results are kept in the smallest kind that holds them exactly,
+// integers grow into bigints, inexact division gives rationals and floats are contagious
+#[derive(Clone)]
+pub enum Number {
+    Int(i64),
+    Big(BigInt),
+    Ratio(BigRational),
+    Float(f64),
+}
+
+impl Number {
+    // reads a numeric literal: 42, -1_000, 0x1F, 0o17, 0b1010, 3.14, .5, 1e9, 7/2.
+    // floats too large to be finite are not numbers
+    pub fn parse(text: &str) -> Option<Number> {
+        let (negative, body) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let digits_from = if body.starts_with('.') { 1 } else { 0 };
+        if !body[digits_from..].starts_with(|c: char| c.is_ascii_digit()) || body.ends_with('_') {
+            return None;
+        }
+        let digits: String = body.chars().filter(|c| *c != '_').collect();
+        let lower = digits.to_ascii_lowercase();
+
+        let mut number;
+        if let Some(radix) = radix_of(&lower) {
+            number = Number::Big(BigInt::parse_bytes(lower[2..].as_bytes(), radix)?);
+        } else if let Some(slash) = lower.find('/') {
+            let numerator = BigInt::parse_bytes(lower[..slash].as_bytes(), 10)?;
+            let denominator = BigInt::parse_bytes(lower[slash + 1..].as_bytes(), 10)?;
+            if denominator.is_zero() {
+                return None;
+            }
+            number = Number::Ratio(BigRational::new(numerator, denominator));
+        } else if lower.contains(|c| c == '.' || c == 'e') {
+            if !lower
+                .chars()
+                .all(|c| c.is_ascii_digit() || ".e+-".contains(c))
+            {
+                return None;
+            }
+            let f = lower.parse::<f64>().ok()?;
+            if !f.is_finite() {
+                return None;
+            }
+            number = Number::Float(f);
+        } else {
+            number = Number::Big(BigInt::parse_bytes(lower.as_bytes(), 10)?);
+        }
+        if negative {
+            number = number.neg();
+        }
+        return Some(number.normalize());
+    }
+
+    // moves a value down the tower when nothing is lost
+    pub fn normalize(self: Self) -> Number {
+        match self {
+            Number::Big(b) => match b.to_i64() {
+                Some(n) => Number::Int(n),
+                None => Number::Big(b),
+            },
+            Number::Ratio(r) => {
+                if r.is_integer() {
+                    return Number::Big(r.to_integer()).normalize();
+                }
+                return Number::Ratio(r);
+            }
+            n => n,
+        }
+    }
+
+    // a machine integer, used for counts and indexes
+    pub fn to_i64(self: &Self) -> Option<i64> {
+        match self {
+            Number::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn to_f64(self: &Self) -> f64 {
+        match self {
+            Number::Int(n) => *n as f64,
+            Number::Big(b) => b.to_f64().unwrap_or(std::f64::NAN),
+            Number::Ratio(r) => r.to_f64().unwrap_or(std::f64::NAN),
+            Number::Float(f) => *f,
+        }
+    }
+
+    fn to_ratio(self: &Self) -> Option<BigRational> {
+        match self {
+            Number::Int(n) => Some(BigRational::from_integer(BigInt::from(*n))),
+            Number::Big(b) => Some(BigRational::from_integer(b.clone())),
+            Number::Ratio(r) => Some(r.clone()),
+            Number::Float(_) => None,
+        }
+    }
+
+    fn is_float(self: &Self) -> bool {
+        if let Number::Float(_) = self {
+            return true;
+        }
+        return false;
+    }
+
+    pub fn is_zero(self: &Self) -> bool {
+        match self {
+            Number::Int(n) => *n == 0,
+            Number::Big(b) => b.is_zero(),
+            Number::Ratio(r) => r.is_zero(),
+            Number::Float(f) => *f == 0.0,
+        }
+    }
+
+    pub fn add(self: &Self, other: &Number) -> Number {
+        if let (Number::Int(a), Number::Int(b)) = (self, other) {
+            if let Some(n) = a.checked_add(*b) {
+                return Number::Int(n);
+            }
+        }
+        return combine(self, other, |a, b| a + b, |a, b| a + b);
+    }
+
+    pub fn sub(self: &Self, other: &Number) -> Number {
+        if let (Number::Int(a), Number::Int(b)) = (self, other) {
+            if let Some(n) = a.checked_sub(*b) {
+                return Number::Int(n);
+            }
+        }
+        return combine(self, other, |a, b| a - b, |a, b| a - b);
+    }
+
+    pub fn mul(self: &Self, other: &Number) -> Number {
+        if let (Number::Int(a), Number::Int(b)) = (self, other) {
+            if let Some(n) = a.checked_mul(*b) {
+                return Number::Int(n);
+            }
+        }
+        return combine(self, other, |a, b| a * b, |a, b| a * b);
+    }
+
+    // exact division, None for a zero divisor
+    pub fn div(self: &Self, other: &Number) -> Option<Number> {
+        if other.is_zero() {
+            return None;
+        }
+        return Some(combine(self, other, |a, b| a / b, |a, b| a / b));
+    }
+
+    // the remainder of truncating division, it takes the sign of the dividend
+    pub fn rem(self: &Self, other: &Number) -> Option<Number> {
+        if other.is_zero() {
+            return None;
+        }
+        return Some(combine(self, other, |a, b| a % b, |a, b| a % b));
+    }
+
+    pub fn neg(self: &Self) -> Number {
+        match self {
+            Number::Int(n) => match n.checked_neg() {
+                Some(m) => Number::Int(m),
+                None => Number::Big(-BigInt::from(*n)),
+            },
+            Number::Big(b) => Number::Big(-b).normalize(),
+            Number::Ratio(r) => Number::Ratio(-r),
+            Number::Float(f) => Number::Float(-f),
+        }
+    }
+
+    pub fn abs(self: &Self) -> Number {
+        match self {
+            Number::Int(n) => match n.checked_abs() {
+                Some(m) => Number::Int(m),
+                None => Number::Big(BigInt::from(*n).abs()),
+            },
+            Number::Big(b) => Number::Big(b.abs()),
+            Number::Ratio(r) => Number::Ratio(r.abs()),
+            Number::Float(f) => Number::Float(f.abs()),
+        }
+    }
+}
+
+fn radix_of(digits: &str) -> Option<u32> {
+    if digits.starts_with("0x") {
+        return Some(16);
+    }
+    if digits.starts_with("0o") {
+        return Some(8);
+    }
+    if digits.starts_with("0b") {
+        return Some(2);
+    }
+    return None;
+}
+
+// applies an operation at the lowest level of the tower both values reach
+fn combine(
+    a: &Number,
+    b: &Number,
+    exact: fn(BigRational, BigRational) -> BigRational,
+    float: fn(f64, f64) -> f64,
+) -> Number {
+    if a.is_float() || b.is_float() {
+        return Number::Float(float(a.to_f64(), b.to_f64()));
+    }
+    let (x, y) = (a.to_ratio().unwrap(), b.to_ratio().unwrap());
+    return Number::Ratio(exact(x, y)).normalize();
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        return self.partial_cmp(other) == Some(Ordering::Equal);
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+        if let (Number::Int(a), Number::Int(b)) = (self, other) {
+            return a.partial_cmp(b);
+        }
+        if self.is_float() || other.is_float() {
+            return self.to_f64().partial_cmp(&other.to_f64());
+        }
+        return self.to_ratio().partial_cmp(&other.to_ratio());
+    }
+}
+
+// written so the lexer reads it back as the same kind of number
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Number::Int(n) => write!(f, "{}", n),
+            Number::Big(b) => write!(f, "{}", b),
+            Number::Ratio(r) => write!(f, "{}/{}", r.numer(), r.denom()),
+            Number::Float(x) => write!(f, "{:?}", x),
+        }
+    }
+}
+
+impl fmt::Debug for Number {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(n: i64) -> Number {
+        return Number::Int(n);
+    }
+}
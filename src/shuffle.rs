@@ -0,0 +1,131 @@
+use std::rc::Rc;
+
+use crate::error::StaplError;
+use crate::eval::{Call, CallStack, Env};
+use crate::lex::{Expectation, LexItem};
+use crate::number::Number;
+
+// forth style words. their stack is the arguments the enclosing call has
+// bound since it was last applied, or the output stack at top level. values a word leaves are
+// returned as its results, so inside a call they are bound again in order
+impl CallStack {
+    pub(crate) fn create_shuffle_words(self: &mut Self) -> () {
+        self.add_builtin("dup", vec![], Rc::new(action_dup));
+        self.add_builtin("drop", vec![], Rc::new(action_drop));
+        self.add_builtin("swap", vec![], Rc::new(action_swap));
+        self.add_builtin("over", vec![], Rc::new(action_over));
+        self.add_builtin("rot", vec![], Rc::new(action_rot));
+        self.add_builtin("pick", vec![Expectation::Num], Rc::new(action_pick));
+        self.add_builtin("depth", vec![], Rc::new(action_depth));
+        self.add_builtin("clear", vec![], Rc::new(action_clear));
+    }
+}
+
+fn depth(env: &Env) -> usize {
+    match &env.caller {
+        Some(caller) => {
+            return caller.matched.len();
+        }
+        None => {
+            return env.ostack.len();
+        }
+    }
+}
+
+// the n newest values, oldest first. a caller gets back the expectations
+// they were bound to
+fn remove_values(call: &Call, env: &mut Env, n: usize) -> Result<Vec<LexItem>, StaplError> {
+    let underflow = StaplError::StackUnderflow {
+        word: call.name.to_string(),
+        span: call.span().clone(),
+    };
+    if depth(env) < n {
+        return Err(underflow);
+    }
+    let mut values = Vec::new();
+    match &mut env.caller {
+        Some(caller) => {
+            for _ in 0..n {
+                if caller.arguments.len() == 0 {
+                    return Err(underflow);
+                }
+                values.insert(0, caller.arguments.remove(0).value);
+                let expectation = caller.matched.pop().unwrap_or(Expectation::Any);
+                caller.expectations.push(expectation);
+            }
+        }
+        None => {
+            let at = env.ostack.len() - n;
+            values = env.ostack.split_off(at);
+        }
+    }
+    return Ok(values);
+}
+
+// returns values oldest first
+fn return_values(call: &mut Call, values: Vec<LexItem>) -> () {
+    for value in values.into_iter().rev() {
+        call.push_result(value);
+    }
+}
+
+// ( a -- a a )
+pub(crate) fn action_dup(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let a = remove_values(call, env, 1)?;
+    return_values(call, vec![a[0].clone(), a[0].clone()]);
+    return Ok(());
+}
+// ( a -- )
+pub(crate) fn action_drop(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    remove_values(call, env, 1)?;
+    return Ok(());
+}
+// ( a b -- b a )
+pub(crate) fn action_swap(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let mut v = remove_values(call, env, 2)?;
+    v.reverse();
+    return_values(call, v);
+    return Ok(());
+}
+// ( a b -- a b a )
+pub(crate) fn action_over(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let mut v = remove_values(call, env, 2)?;
+    v.push(v[0].clone());
+    return_values(call, v);
+    return Ok(());
+}
+// ( a b c -- b c a )
+pub(crate) fn action_rot(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let mut v = remove_values(call, env, 3)?;
+    v.rotate_left(1);
+    return_values(call, v);
+    return Ok(());
+}
+// pick n ( xn .. x0 -- xn .. x0 xn ), pick 0 is dup
+pub(crate) fn action_pick(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let n = call.pop_int()?;
+    if n < 0 || n as usize >= depth(env) {
+        return Err(StaplError::IndexOutOfRange {
+            word: call.name.to_string(),
+            index: n,
+            len: depth(env),
+            span: call.span().clone(),
+        });
+    }
+    let mut v = remove_values(call, env, n as usize + 1)?;
+    v.push(v[0].clone());
+    return_values(call, v);
+    return Ok(());
+}
+// ( -- n ) the number of values there were before it
+pub(crate) fn action_depth(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let n = depth(env);
+    call.push_result(LexItem::Num(Number::from(n as i64), call.span().clone()));
+    return Ok(());
+}
+// ( .. -- )
+pub(crate) fn action_clear(call: &mut Call, env: &mut Env) -> Result<(), StaplError> {
+    let n = depth(env);
+    remove_values(call, env, n)?;
+    return Ok(());
+}
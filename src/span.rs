@@ -0,0 +1,115 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+// a named piece of source text, shared by the spans lexed from it
+pub struct Source {
+    pub name: String,
+    pub text: String,
+    line_starts: Vec<usize>,
+}
+
+impl Source {
+    pub fn new(name: &str, text: &str) -> Source {
+        let mut line_starts = vec![0];
+        for (i, c) in text.char_indices() {
+            if c == '\n' {
+                line_starts.push(i + 1);
+            }
+        }
+        return Source {
+            name: name.to_string(),
+            text: text.to_string(),
+            line_starts: line_starts,
+        };
+    }
+
+    // the text of a 1-based line, without its line ending
+    pub fn line(self: &Self, line: usize) -> &str {
+        if line == 0 || line > self.line_starts.len() {
+            return "";
+        }
+        let start = self.line_starts[line - 1];
+        let end = match self.line_starts.get(line) {
+            Some(next) => *next,
+            None => self.text.len(),
+        };
+        return self.text[start..end].trim_end_matches(|c| c == '\n' || c == '\r');
+    }
+
+    fn locate(self: &Self, offset: usize) -> (usize, usize) {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        let column = self.text[self.line_starts[line]..offset].chars().count();
+        return (line + 1, column + 1);
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Span {
+    pub source: Option<Rc<Source>>,
+    pub line: usize,
+    pub column: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(source: &Rc<Source>, start: usize, end: usize) -> Span {
+        let (line, column) = source.locate(start);
+        return Span {
+            source: Some(source.clone()),
+            line: line,
+            column: column,
+            start: start,
+            end: end,
+        };
+    }
+
+    // a span covering both self and a later span from the same source
+    pub fn join(self: &Self, other: &Span) -> Span {
+        match (&self.source, &other.source) {
+            (Some(a), Some(b)) if Rc::ptr_eq(a, b) => {
+                return Span::new(a, self.start, other.end);
+            }
+            (Some(_), _) => {
+                return self.clone();
+            }
+            _ => {
+                return other.clone();
+            }
+        }
+    }
+
+    pub fn is_known(self: &Self) -> bool {
+        return self.source.is_some();
+    }
+}
+
+// spans record where a value came from, they are not part of the value
+impl PartialEq for Span {
+    fn eq(&self, _other: &Span) -> bool {
+        true
+    }
+}
+impl PartialOrd for Span {
+    fn partial_cmp(&self, _other: &Span) -> Option<Ordering> {
+        Some(Ordering::Equal)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.source {
+            Some(s) => write!(f, "{}:{}:{}", s.name, self.line, self.column),
+            None => write!(f, "<unknown>"),
+        }
+    }
+}
+impl fmt::Debug for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
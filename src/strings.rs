@@ -0,0 +1,131 @@
+use std::rc::Rc;
+
+use crate::error::StaplError;
+use crate::eval::{Call, CallStack, Env};
+use crate::lex::{Expectation, LexItem};
+use crate::number::Number;
+
+impl CallStack {
+    pub(crate) fn create_string_words(self: &mut Self) -> () {
+        // len and concat take stacks as well and are with the list words
+        self.add_builtin(
+            "substr",
+            vec![Expectation::Num, Expectation::Num, Expectation::Str],
+            Rc::new(action_substr),
+        );
+        self.add_builtin(
+            "split",
+            vec![Expectation::Str, Expectation::Str],
+            Rc::new(action_split),
+        );
+        self.add_builtin(
+            "join",
+            vec![Expectation::Str, Expectation::Stack],
+            Rc::new(action_join),
+        );
+        self.add_builtin("upper", vec![Expectation::Str], Rc::new(action_upper));
+        self.add_builtin("lower", vec![Expectation::Str], Rc::new(action_lower));
+        self.add_builtin(
+            "str->num",
+            vec![Expectation::Str],
+            Rc::new(action_str_to_num),
+        );
+        self.add_builtin(
+            "num->str",
+            vec![Expectation::Num],
+            Rc::new(action_num_to_str),
+        );
+    }
+}
+
+// substr "text" start length, counted in characters from 0
+pub(crate) fn action_substr(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let s = call.pop_str()?;
+    let start = call.pop_int()?;
+    let length = call.pop_int()?;
+    let len = s.chars().count();
+    if start < 0 || start as usize > len {
+        return Err(StaplError::IndexOutOfRange {
+            word: call.name.to_string(),
+            index: start,
+            len: len,
+            span: call.span().clone(),
+        });
+    }
+    if length < 0 || length as usize > len - start as usize {
+        return Err(StaplError::IndexOutOfRange {
+            word: call.name.to_string(),
+            index: start.saturating_add(length),
+            len: len,
+            span: call.span().clone(),
+        });
+    }
+    let sub: String = s
+        .chars()
+        .skip(start as usize)
+        .take(length as usize)
+        .collect();
+    call.push_result(LexItem::Str(sub, call.span().clone()));
+    return Ok(());
+}
+pub(crate) fn action_split(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let s = call.pop_str()?;
+    let separator = call.pop_str()?;
+    let span = call.span().clone();
+    let mut parts: Vec<LexItem> = Vec::new();
+    if separator.len() == 0 {
+        for c in s.chars() {
+            parts.push(LexItem::Str(c.to_string(), span.clone()));
+        }
+    } else {
+        for part in s.split(separator.as_str()) {
+            parts.push(LexItem::Str(part.to_string(), span.clone()));
+        }
+    }
+    // stacks keep their first element on top
+    parts.reverse();
+    call.push_result(LexItem::Stack(parts, span));
+    return Ok(());
+}
+pub(crate) fn action_join(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let stack = call.pop_stack()?;
+    let separator = call.pop_str()?;
+    let mut parts = Vec::new();
+    for item in stack.iter().rev() {
+        match item {
+            LexItem::Str(s, _) => {
+                parts.push(s.to_string());
+            }
+            _ => {
+                return Err(call.type_mismatch(Expectation::Str, item.clone()));
+            }
+        }
+    }
+    call.push_result(LexItem::Str(parts.join(&separator), call.span().clone()));
+    return Ok(());
+}
+pub(crate) fn action_upper(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let s = call.pop_str()?;
+    call.push_result(LexItem::Str(s.to_uppercase(), call.span().clone()));
+    return Ok(());
+}
+pub(crate) fn action_lower(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let s = call.pop_str()?;
+    call.push_result(LexItem::Str(s.to_lowercase(), call.span().clone()));
+    return Ok(());
+}
+pub(crate) fn action_str_to_num(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let value = call.pop_argument()?;
+    if let LexItem::Str(s, _) = &value {
+        if let Some(n) = Number::parse(s.trim()) {
+            call.push_result(LexItem::Num(n, call.span().clone()));
+            return Ok(());
+        }
+    }
+    return Err(call.type_mismatch(Expectation::Num, value));
+}
+pub(crate) fn action_num_to_str(call: &mut Call, _env: &mut Env) -> Result<(), StaplError> {
+    let n = call.pop_num()?;
+    call.push_result(LexItem::Str(n.to_string(), call.span().clone()));
+    return Ok(());
+}
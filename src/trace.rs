@@ -0,0 +1,266 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::format::{format_expstack, format_lexstack, format_source};
+use crate::lex::{Expectation, LexItem};
+use crate::span::Span;
+
+// how much of an evaluation is reported, each level includes the ones before it
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum TraceLevel {
+    Off,
+    Summary,
+    Steps,
+    Full,
+}
+
+impl FromStr for TraceLevel {
+    type Err = String;
+    fn from_str(s: &str) -> Result<TraceLevel, String> {
+        match s {
+            "off" => Ok(TraceLevel::Off),
+            "summary" => Ok(TraceLevel::Summary),
+            "steps" => Ok(TraceLevel::Steps),
+            "full" => Ok(TraceLevel::Full),
+            _ => Err(format!(
+                "unknown trace level {}, expected off, summary, steps or full",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TraceLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            TraceLevel::Off => "off",
+            TraceLevel::Summary => "summary",
+            TraceLevel::Steps => "steps",
+            TraceLevel::Full => "full",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TraceFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<TraceFormat, String> {
+        match s {
+            "text" => Ok(TraceFormat::Text),
+            "json" => Ok(TraceFormat::Json),
+            _ => Err(format!("unknown trace format {}, expected text or json", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TraceEvent {
+    // steps: a word was looked up and is waiting for its arguments
+    CallPushed {
+        word: String,
+        depth: usize,
+        expectations: Vec<Expectation>,
+        span: Span,
+    },
+    // full: a value was taken as an argument by the innermost call
+    ArgumentBound {
+        word: String,
+        name: String,
+        value: LexItem,
+    },
+    // steps: a builtin ran and returned its results to the input
+    Applied {
+        word: String,
+        results: Vec<LexItem>,
+    },
+    // steps: a user word or lambda was expanded into its body
+    Substituted {
+        word: String,
+        body: Vec<LexItem>,
+    },
+    // full: a value nobody asked for went to the output stack
+    Output {
+        value: LexItem,
+    },
+    // summary: one evaluation has ended, error holds the message if it failed
+    Finished {
+        steps: u64,
+        elapsed: Duration,
+        outputs: usize,
+        error: Option<String>,
+    },
+}
+
+impl TraceEvent {
+    pub fn kind(self: &Self) -> &'static str {
+        match self {
+            TraceEvent::CallPushed { .. } => "call_pushed",
+            TraceEvent::ArgumentBound { .. } => "argument_bound",
+            TraceEvent::Applied { .. } => "applied",
+            TraceEvent::Substituted { .. } => "substituted",
+            TraceEvent::Output { .. } => "output",
+            TraceEvent::Finished { .. } => "finished",
+        }
+    }
+
+    pub fn to_text(self: &Self) -> String {
+        match self {
+            TraceEvent::CallPushed {
+                word,
+                depth,
+                expectations,
+                span,
+            } => format!(
+                "[{}] push {} at {} expecting {}",
+                depth,
+                word,
+                span,
+                format_expstack(expectations).trim_end()
+            ),
+            TraceEvent::ArgumentBound { word, name, value } => {
+                format!("bind {} ${} = {}", word, name, format_source(value))
+            }
+            TraceEvent::Applied { word, results } => {
+                format!("apply {} -> {}", word, format_lexstack(results))
+            }
+            TraceEvent::Substituted { word, body } => {
+                format!("substitute {} -> {}", word, format_lexstack(body))
+            }
+            TraceEvent::Output { value } => format!("output {}", format_source(value)),
+            TraceEvent::Finished {
+                steps,
+                elapsed,
+                outputs,
+                error,
+            } => {
+                let mut text = format!(
+                    "finished in {} steps, {:?}, {} outputs",
+                    steps, elapsed, outputs
+                );
+                if let Some(e) = error {
+                    text = format!("{}, error: {}", text, e);
+                }
+                text
+            }
+        }
+    }
+
+    // one JSON object per event, values are written in their printed form
+    pub fn to_json(self: &Self) -> String {
+        let mut fields = vec![("event", json_string(self.kind()))];
+        match self {
+            TraceEvent::CallPushed {
+                word,
+                depth,
+                expectations,
+                span,
+            } => {
+                fields.push(("word", json_string(word)));
+                fields.push(("depth", depth.to_string()));
+                fields.push((
+                    "expects",
+                    json_string(format_expstack(expectations).trim_end()),
+                ));
+                fields.push(("at", json_string(&span.to_string())));
+            }
+            TraceEvent::ArgumentBound { word, name, value } => {
+                fields.push(("word", json_string(word)));
+                fields.push(("name", json_string(name)));
+                fields.push(("value", json_string(&format_source(value))));
+            }
+            TraceEvent::Applied { word, results } => {
+                fields.push(("word", json_string(word)));
+                fields.push(("results", json_string(&format_lexstack(results))));
+            }
+            TraceEvent::Substituted { word, body } => {
+                fields.push(("word", json_string(word)));
+                fields.push(("body", json_string(&format_lexstack(body))));
+            }
+            TraceEvent::Output { value } => {
+                fields.push(("value", json_string(&format_source(value))));
+            }
+            TraceEvent::Finished {
+                steps,
+                elapsed,
+                outputs,
+                error,
+            } => {
+                fields.push(("steps", steps.to_string()));
+                fields.push(("elapsed_us", elapsed.as_micros().to_string()));
+                fields.push(("outputs", outputs.to_string()));
+                if let Some(e) = error {
+                    fields.push(("error", json_string(e)));
+                }
+            }
+        }
+        let body: Vec<String> = fields
+            .iter()
+            .map(|(k, v)| format!("\"{}\":{}", k, v))
+            .collect();
+        return format!("{{{}}}", body.join(","));
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = "\"".to_string();
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    return out;
+}
+
+pub type TraceSink = Box<dyn FnMut(&TraceEvent)>;
+
+// events go to the sink if one is set, otherwise to stderr in the chosen format
+pub(crate) struct Tracer {
+    pub(crate) level: TraceLevel,
+    pub(crate) format: TraceFormat,
+    pub(crate) sink: Option<TraceSink>,
+}
+
+impl Tracer {
+    pub(crate) fn new() -> Tracer {
+        return Tracer {
+            level: TraceLevel::Off,
+            format: TraceFormat::Text,
+            sink: None,
+        };
+    }
+
+    // the event is only built when its level is being traced
+    pub(crate) fn emit<F: FnOnce() -> TraceEvent>(
+        self: &mut Self,
+        level: TraceLevel,
+        event: F,
+    ) -> () {
+        if self.level == TraceLevel::Off || level > self.level {
+            return;
+        }
+        let event = event();
+        match &mut self.sink {
+            Some(sink) => {
+                sink(&event);
+            }
+            None => match self.format {
+                TraceFormat::Text => eprintln!("trace: {}", event.to_text()),
+                TraceFormat::Json => eprintln!("{}", event.to_json()),
+            },
+        }
+    }
+}
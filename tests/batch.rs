@@ -0,0 +1,53 @@
+use std::fs;
+
+mod common;
+
+use common::third;
+
+#[test]
+fn expressions_share_one_interpreter() {
+    let (stdout, _, code) = third(&["-e", "define sq [ * $1 $1 ]", "-e", "sq 3"]);
+    assert_eq!(stdout, "\n9\n");
+    assert_eq!(code, 0);
+}
+
+#[test]
+fn files_share_one_interpreter_and_report_their_own_name() {
+    let dir = std::env::temp_dir().join(format!("third-batch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let prelude = dir.join("prelude.stp");
+    let main = dir.join("main.stp");
+    fs::write(&prelude, "define sq [ * $1 $1 ]\n").unwrap();
+    fs::write(&main, "sq 4\nunknown").unwrap();
+    let (stdout, stderr, code) = third(&[prelude.to_str().unwrap(), main.to_str().unwrap()]);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(stdout, "\n");
+    assert!(
+        stderr.contains("main.stp:2:1: error: unknown word unknown"),
+        "{}",
+        stderr
+    );
+    assert_eq!(code, 1);
+}
+
+#[test]
+fn a_timeout_must_be_a_duration() {
+    for seconds in &["-1", "inf", "NaN"] {
+        let (_, stderr, code) = third(&["--timeout", seconds, "-e", "1"]);
+        assert_eq!(stderr, format!("--timeout: invalid limit {}\n", seconds));
+        assert_eq!(code, 2);
+    }
+    let (stdout, _, code) = third(&["--timeout", "0.5", "-e", "1"]);
+    assert_eq!(stdout, "1\n");
+    assert_eq!(code, 0);
+}
+
+#[test]
+fn unmatched_brackets_stop_the_batch() {
+    for source in &["1 ] + 2 3", "[ 1"] {
+        let (stdout, stderr, code) = third(&["-e", source]);
+        assert_eq!(stdout, "");
+        assert!(stderr.contains("syntax error"), "{}", stderr);
+        assert_eq!(code, 1);
+    }
+}
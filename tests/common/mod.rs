@@ -0,0 +1,39 @@
+// helpers shared by the integration tests, each test file uses some of them
+#![allow(dead_code)]
+
+use std::process::Command;
+use third::{format_values, Interpreter, StaplError};
+
+// the values an expression outputs, as the REPL prints them
+pub fn eval(source: &str) -> String {
+    let mut interpreter = Interpreter::new("test");
+    return format_values(&interpreter.eval_str(source).unwrap());
+}
+
+// the values the last of several expressions outputs, evaluated one after another
+pub fn eval_each(sources: &[&str]) -> String {
+    let mut interpreter = Interpreter::new("test");
+    let mut values = Vec::new();
+    for source in sources {
+        values = interpreter.eval_str(source).unwrap();
+    }
+    return format_values(&values);
+}
+
+pub fn error(source: &str) -> StaplError {
+    let mut interpreter = Interpreter::new("test");
+    return interpreter.eval_str(source).unwrap_err();
+}
+
+// runs the binary, returning what it printed and its exit code
+pub fn third(args: &[&str]) -> (String, String, i32) {
+    let output = Command::new(env!("CARGO_BIN_EXE_third"))
+        .args(args)
+        .output()
+        .unwrap();
+    return (
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        output.status.code().unwrap_or(-1),
+    );
+}
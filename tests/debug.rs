@@ -0,0 +1,160 @@
+use third::{
+    format_source, format_values, DebugCommand, DebugStop, Interpreter, Pending, StaplError,
+};
+
+const SQ: &str = "define sq [ * $1 $1 ]";
+
+fn debugger(source: &str) -> Interpreter {
+    let mut interpreter = Interpreter::new("test");
+    interpreter.eval_str(SQ).unwrap();
+    interpreter.debug_start(source).unwrap();
+    return interpreter;
+}
+
+// what the debugger will do next, as its view shows it
+fn next(interpreter: &Interpreter) -> String {
+    match interpreter.debug_pending() {
+        Pending::Apply(w) => {
+            return format!("apply {}", w);
+        }
+        Pending::Push(item) => {
+            return format!("push {}", format_source(&item));
+        }
+        Pending::Done => {
+            return "done".to_string();
+        }
+    }
+}
+
+fn finished(stop: DebugStop) -> String {
+    match stop {
+        DebugStop::Finished(values) => {
+            return format_values(&values);
+        }
+        other => panic!("expected the evaluation to finish, got {:?}", other),
+    }
+}
+
+#[test]
+fn step_stops_before_each_push_and_apply() {
+    let mut interpreter = debugger("+ 1 sq 3");
+    let mut seen = vec![next(&interpreter)];
+    loop {
+        match interpreter.debug_run(DebugCommand::Step).unwrap() {
+            DebugStop::Paused => {
+                seen.push(next(&interpreter));
+            }
+            stop => {
+                assert_eq!(finished(stop), "10");
+                break;
+            }
+        }
+    }
+    assert_eq!(
+        seen,
+        vec![
+            "push +", "push 1", "push sq", "push 3", "apply sq", "push *", "push 3", "push 3",
+            "apply *", "push 9", "apply +", "push 10",
+        ]
+    );
+}
+
+#[test]
+fn next_runs_the_call_a_step_starts() {
+    let mut interpreter = debugger("+ 1 sq 3");
+    interpreter.debug_run(DebugCommand::Step).unwrap();
+    interpreter.debug_run(DebugCommand::Step).unwrap();
+    assert_eq!(next(&interpreter), "push sq");
+    // sq takes its argument and is applied, its body is left to step through
+    interpreter.debug_run(DebugCommand::Next).unwrap();
+    assert_eq!(next(&interpreter), "push *");
+    interpreter.debug_run(DebugCommand::Next).unwrap();
+    assert_eq!(next(&interpreter), "push 9");
+    // an item that starts no call is a single step
+    interpreter.debug_run(DebugCommand::Next).unwrap();
+    assert_eq!(next(&interpreter), "apply +");
+}
+
+#[test]
+fn finish_runs_until_the_innermost_call_is_applied() {
+    let mut interpreter = debugger("+ 1 sq 3");
+    for _ in 0..6 {
+        interpreter.debug_run(DebugCommand::Step).unwrap();
+    }
+    assert_eq!(next(&interpreter), "push 3");
+    assert!(
+        interpreter.debug_view().contains("[2] *"),
+        "{}",
+        interpreter.debug_view()
+    );
+    interpreter.debug_run(DebugCommand::Finish).unwrap();
+    assert_eq!(next(&interpreter), "push 9");
+    interpreter.debug_run(DebugCommand::Finish).unwrap();
+    assert_eq!(next(&interpreter), "push 10");
+    // with no call pending it runs to the end
+    assert_eq!(
+        finished(interpreter.debug_run(DebugCommand::Finish).unwrap()),
+        "10"
+    );
+}
+
+#[test]
+fn continue_runs_to_the_end() {
+    let mut interpreter = debugger("+ 1 sq 3 sq 4");
+    assert_eq!(
+        finished(interpreter.debug_run(DebugCommand::Continue).unwrap()),
+        "10 16"
+    );
+}
+
+#[test]
+fn a_breakpoint_stops_once_before_each_call_is_applied() {
+    let mut interpreter = Interpreter::new("test");
+    interpreter.eval_str(SQ).unwrap();
+    interpreter.add_breakpoint("sq");
+    interpreter.add_breakpoint("sq");
+    assert_eq!(interpreter.breakpoints(), &vec!["sq".to_string()]);
+    interpreter.debug_start("+ sq 2 sq 3").unwrap();
+    let mut args = Vec::new();
+    loop {
+        match interpreter.debug_run(DebugCommand::Continue).unwrap() {
+            DebugStop::Breakpoint(word) => {
+                assert_eq!(word, "sq");
+                assert_eq!(next(&interpreter), "apply sq");
+                let view = interpreter.debug_view();
+                let call = view
+                    .lines()
+                    .find(|l| l.contains("sq expects"))
+                    .unwrap()
+                    .to_string();
+                args.push(call.split("args: ").nth(1).unwrap().to_string());
+            }
+            stop => {
+                assert_eq!(finished(stop), "13");
+                break;
+            }
+        }
+    }
+    assert_eq!(args, vec!["($1) 2", "($1) 3"]);
+    assert!(interpreter.remove_breakpoint("sq"));
+    assert!(!interpreter.remove_breakpoint("sq"));
+    interpreter.debug_start("sq 2").unwrap();
+    assert_eq!(
+        finished(interpreter.debug_run(DebugCommand::Continue).unwrap()),
+        "4"
+    );
+}
+
+#[test]
+fn an_error_ends_the_session() {
+    let mut interpreter = debugger("+ 1 / 1 0");
+    match interpreter.debug_run(DebugCommand::Continue) {
+        Err(StaplError::DivisionByZero { .. }) => {}
+        other => panic!("expected a division by zero, got {:?}", other),
+    }
+    assert_eq!(next(&interpreter), "done");
+    match interpreter.debug_start("[ 1") {
+        Err(StaplError::Syntax { .. }) => {}
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+}
@@ -0,0 +1,63 @@
+use third::{format_values, Interpreter, StaplError};
+
+fn eval(interpreter: &mut Interpreter, source: &str) -> String {
+    return format_values(&interpreter.eval_str(source).unwrap());
+}
+
+#[test]
+fn definitions_read_back_as_source() {
+    let mut interpreter = Interpreter::new("test");
+    assert_eq!(interpreter.definitions_source(), "");
+    eval(
+        &mut interpreter,
+        "define sq [ * $1 $1 ] define greeting \"hi\\n\" define m { 'a '[ 1 ] }",
+    );
+    let source = interpreter.definitions_source();
+    assert_eq!(
+        source,
+        "define greeting \"hi\\n\"\ndefine m { 'a '[ 1 ] }\ndefine sq [ * $1 $1 ]\n"
+    );
+    let mut copy = Interpreter::new("test");
+    eval(&mut copy, &source);
+    assert_eq!(eval(&mut copy, "sq 4 greeting"), "16 \"hi\\n\"");
+    for name in ["sq", "greeting", "m"].iter() {
+        assert_eq!(
+            copy.definition(name),
+            interpreter.definition(name),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn forget_removes_one_word() {
+    let mut interpreter = Interpreter::new("test");
+    eval(&mut interpreter, "define sq [ * $1 $1 ] define one 1");
+    assert!(interpreter.forget("sq"));
+    assert!(!interpreter.forget("sq"));
+    assert!(interpreter.definition("sq").is_none());
+    match interpreter.eval_str("sq") {
+        Err(StaplError::UnknownWord { .. }) => {}
+        other => panic!("expected sq to be unknown, got {:?}", other),
+    }
+    assert_eq!(eval(&mut interpreter, "one"), "1");
+    // builtins can be forgotten as well
+    assert!(interpreter.forget("+"));
+    assert!(!interpreter.words().contains(&"+".to_string()));
+}
+
+#[test]
+fn reset_leaves_only_the_builtins() {
+    let mut interpreter = Interpreter::new("test");
+    let builtins = interpreter.words();
+    eval(&mut interpreter, "define sq [ * $1 $1 ]");
+    interpreter.forget("+");
+    interpreter.register_word("host", vec![], |_call, _env| {
+        return Ok(());
+    });
+    interpreter.reset_dictionary();
+    assert_eq!(interpreter.words(), builtins);
+    assert_eq!(interpreter.definitions_source(), "");
+    assert_eq!(eval(&mut interpreter, "+ 1 2"), "3");
+}
@@ -0,0 +1,67 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use third::{format_values, Call, Env, Expectation, Interpreter, LexItem, Number, StaplError};
+
+#[test]
+fn registered_words_can_keep_state_between_calls() {
+    let mut interpreter = Interpreter::new("test");
+    let count = Rc::new(Cell::new(0));
+    let counter = count.clone();
+    interpreter.register_word("tick", vec![], move |call: &mut Call, _env: &mut Env| {
+        counter.set(counter.get() + 1);
+        call.push_result(LexItem::Num(
+            Number::from(counter.get()),
+            call.span().clone(),
+        ));
+        return Ok(());
+    });
+    let values = interpreter.eval_str("tick tick + tick 10").unwrap();
+    assert_eq!(format_values(&values), "1 2 13");
+    assert_eq!(count.get(), 3);
+}
+
+#[test]
+fn registered_words_take_arguments_and_can_fail() {
+    let mut interpreter = Interpreter::new("test");
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let log = seen.clone();
+    // log STR NUM
+    let expectations = vec![Expectation::Str, Expectation::Num];
+    interpreter.register_word(
+        "log",
+        expectations,
+        move |call: &mut Call, _env: &mut Env| {
+            let label = call.pop_str()?;
+            let n = call.pop_num()?;
+            if n.is_zero() {
+                return Err(StaplError::DivisionByZero {
+                    word: "log".to_string(),
+                    span: call.span().clone(),
+                });
+            }
+            log.borrow_mut().push(format!("{} {}", label, n));
+            return Ok(());
+        },
+    );
+    interpreter.eval_str("log \"a\" 1 log \"b\" / 1 2").unwrap();
+    assert!(interpreter.eval_str("log \"c\" 0").is_err());
+    assert_eq!(*seen.borrow(), vec!["a 1".to_string(), "b 1/2".to_string()]);
+    assert_eq!(
+        format!("{:?}", interpreter.word_expectations("log").unwrap()),
+        "[Str, Num]"
+    );
+}
+
+#[test]
+fn errors_can_be_boxed_by_the_host() {
+    fn run(source: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut interpreter = Interpreter::new("test");
+        return Ok(interpreter.eval_str(source)?.len());
+    }
+    assert_eq!(run("1 2").unwrap(), 2);
+    assert_eq!(
+        run("/ 1 0").unwrap_err().to_string(),
+        "division by zero in /"
+    );
+}
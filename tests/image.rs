@@ -0,0 +1,93 @@
+use third::{parse, Interpreter};
+
+const IMAGE: &str = "stapl-image 1\nsq Value = [ * $1 $1 ]\n";
+
+#[test]
+fn images_keep_definitions() {
+    let mut interpreter = Interpreter::new("test");
+    interpreter
+        .eval_str("define sq [ * $1 $1 ] define greeting \"a \\\"b\\\"\" define m { 1 [ $x ] }")
+        .unwrap();
+    let mut restored = Interpreter::new("test");
+    assert_eq!(restored.load_image(&interpreter.image()), Ok(3));
+    for name in ["sq", "greeting", "m"].iter() {
+        assert_eq!(
+            restored.definition(name),
+            interpreter.definition(name),
+            "{}",
+            name
+        );
+        assert_eq!(
+            restored.word_expectations(name).map(|e| format!("{:?}", e)),
+            interpreter
+                .word_expectations(name)
+                .map(|e| format!("{:?}", e))
+        );
+    }
+    assert_eq!(restored.eval_str("sq 7").unwrap(), parse("49").unwrap());
+}
+
+#[test]
+fn expectations_are_written_first_argument_first() {
+    let mut interpreter = Interpreter::new("test");
+    interpreter
+        .load_image("stapl-image 1\n\nlog Str Num = [ $1 ]\n")
+        .unwrap();
+    assert_eq!(
+        format!("{:?}", interpreter.word_expectations("log").unwrap()),
+        "[Str, Num]"
+    );
+    assert_eq!(interpreter.image(), "stapl-image 1\nlog Str Num = [ $1 ]\n");
+}
+
+#[test]
+fn an_image_needs_its_header() {
+    let mut interpreter = Interpreter::new("test");
+    for image in &["", "sq Value = [ * $1 $1 ]\n", "stapl-image 2\n"] {
+        assert_eq!(
+            interpreter.load_image(image),
+            Err("not an image, expected stapl-image 1 on the first line".to_string())
+        );
+    }
+}
+
+#[test]
+fn malformed_lines_are_reported_with_their_number() {
+    let mut interpreter = Interpreter::new("test");
+    let errors = [
+        (
+            "sq Value [ * $1 $1 ]",
+            "line 4: expected name, expectations, = and a value",
+        ),
+        (" = 1", "line 4: missing word name"),
+        (
+            "sq Number = [ * $1 $1 ]",
+            "line 4: unknown expectation Number",
+        ),
+        ("two = 1 2", "line 4: 1 2 is not a single value"),
+        ("none = ", "line 4: missing value"),
+        ("open = [ 1", "line 4: [ 1 is not a single value"),
+    ];
+    for (line, error) in errors.iter() {
+        let image = format!("{}one = 1\n{}\n", IMAGE, line);
+        assert_eq!(
+            interpreter.load_image(&image),
+            Err(error.to_string()),
+            "{}",
+            line
+        );
+    }
+}
+
+#[test]
+fn nothing_is_loaded_from_a_malformed_image() {
+    let mut interpreter = Interpreter::new("test");
+    interpreter.eval_str("define sq [ + $1 $1 ]").unwrap();
+    let image = format!("{}cube Value = [ * $1 sq $1 ]\nbad\n", IMAGE);
+    assert!(interpreter.load_image(&image).is_err());
+    assert_eq!(interpreter.definition("cube"), None);
+    assert_eq!(interpreter.eval_str("sq 3").unwrap(), parse("6").unwrap());
+    // a good image replaces words with the same name
+    assert_eq!(interpreter.load_image(IMAGE), Ok(1));
+    assert_eq!(interpreter.eval_str("sq 3").unwrap(), parse("9").unwrap());
+}
@@ -0,0 +1,136 @@
+use third::{format_values, EvalLimits, Interpreter, Limit, StaplError};
+
+mod common;
+
+use common::{error, eval};
+
+#[test]
+fn map_filter_and_fold_go_first_element_first() {
+    assert_eq!(eval("map [ * $1 10 ] '[ 1 2 3 ]"), "'[ 10 20 30 ]");
+    assert_eq!(eval("filter [ > $1 1 ] '[ 1 2 3 ]"), "'[ 2 3 ]");
+    assert_eq!(
+        eval("fold [ concat $1 $2 ] \"\" '[ \"a\" \"b\" \"c\" ]"),
+        "\"abc\""
+    );
+    assert_eq!(eval("fold [ - $1 $2 ] 0 '[ 1 2 3 ]"), "-6");
+}
+
+#[test]
+fn higher_order_words_on_empty_stacks() {
+    assert_eq!(eval("map [ * $1 10 ] '[ ]"), "'[ ]");
+    assert_eq!(eval("filter [ > $1 1 ] '[ ]"), "'[ ]");
+    assert_eq!(eval("fold [ + $1 $2 ] 7 '[ ]"), "7");
+}
+
+#[test]
+fn errors_in_a_lambda_name_the_word_not_its_state() {
+    let mut interpreter = Interpreter::new("test");
+    for word in &["map", "filter"] {
+        let source = format!("{} [ + $1 \"a\" ] '[ 1 2 ]", word);
+        interpreter.eval_str(&source).unwrap_err();
+        assert_eq!(
+            interpreter.backtrace(),
+            &vec!["+".to_string(), word.to_string()]
+        );
+    }
+    interpreter
+        .eval_str("fold [ + $1 $2 ] 0 '[ 1 \"a\" ]")
+        .unwrap_err();
+    assert_eq!(
+        interpreter.backtrace(),
+        &vec!["+".to_string(), "fold".to_string()]
+    );
+}
+
+#[test]
+fn slice_bounds() {
+    assert_eq!(eval("slice '[ 1 2 3 4 ] 1 2"), "'[ 2 3 ]");
+    assert_eq!(eval("slice '[ 1 2 3 ] 3 0"), "'[ ]");
+    assert_eq!(eval("slice '[ 1 2 3 ] 0 3"), "'[ 1 2 3 ]");
+    for source in &[
+        "slice '[ 1 2 3 ] 2 2",
+        "slice '[ 1 2 3 ] -1 1",
+        "slice '[ 1 2 3 ] 4 0",
+    ] {
+        match error(source) {
+            StaplError::IndexOutOfRange { len, .. } => {
+                assert_eq!(len, 3);
+            }
+            other => panic!("expected {} to be out of range, got {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn sort_takes_only_numbers_or_only_strings() {
+    assert_eq!(eval("sort '[ 3 1 2 ]"), "'[ 1 2 3 ]");
+    assert_eq!(
+        eval("sort '[ \"b\" \"c\" \"a\" ]"),
+        "'[ \"a\" \"b\" \"c\" ]"
+    );
+    assert_eq!(eval("sort '[ ]"), "'[ ]");
+    for source in &["sort '[ 1 \"a\" ]", "sort '[ \"a\" 1 ]", "sort '[ '[ 1 ] ]"] {
+        match error(source) {
+            StaplError::TypeMismatch { word, .. } => {
+                assert_eq!(word, "sort");
+            }
+            other => panic!("expected {} to be a type mismatch, got {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn zip_stops_at_the_shorter_stack() {
+    assert_eq!(
+        eval("zip '[ 1 2 3 ] '[ \"a\" \"b\" ]"),
+        "'[ '[ 1 \"a\" ] '[ 2 \"b\" ] ]"
+    );
+    assert_eq!(eval("zip '[ ] '[ 1 ]"), "'[ ]");
+}
+
+#[test]
+fn flatten_removes_one_level() {
+    assert_eq!(
+        eval("flatten '[ 1 '[ 2 3 ] '[ '[ 4 ] ] ]"),
+        "'[ 1 2 3 '[ 4 ] ]"
+    );
+    assert_eq!(eval("flatten '[ '[ ] ]"), "'[ ]");
+}
+
+#[test]
+fn range_counts_up_to_its_end() {
+    assert_eq!(eval("range 2 5"), "'[ 2 3 4 ]");
+    assert_eq!(eval("range 5 2"), "'[ ]");
+}
+
+#[test]
+fn range_larger_than_the_item_limit_is_an_error() {
+    let mut interpreter = Interpreter::new("test");
+    let mut limits = EvalLimits::default();
+    limits.max_stack_items = Some(1_000);
+    interpreter.set_limits(limits);
+    match interpreter.eval_str("range 0 100000000000") {
+        Err(StaplError::LimitExceeded { limit, .. }) => {
+            assert_eq!(limit, Limit::StackItems(1_000));
+        }
+        other => panic!("expected the item limit, got {:?}", other),
+    }
+    assert_eq!(
+        format_values(&interpreter.eval_str("len range 0 999").unwrap()),
+        "999"
+    );
+}
+
+#[test]
+fn take_needs_a_count_of_zero_or_more() {
+    assert_eq!(eval("take 0"), "'[ ]");
+    assert_eq!(eval("take 2 1 2"), "'[ 1 2 ]");
+    match error("take -1 1") {
+        StaplError::NegativeCount { word, count, .. } => {
+            assert_eq!(word, "take");
+            assert_eq!(count, -1);
+        }
+        other => panic!("expected a negative count, got {:?}", other),
+    }
+    assert_eq!(error("take -3").to_string(), "negative count in take: -3");
+}
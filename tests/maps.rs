@@ -0,0 +1,99 @@
+use third::StaplError;
+
+mod common;
+
+use common::{error, eval};
+
+#[test]
+fn get_and_has() {
+    assert_eq!(eval("get { 'a 1 'b 2 } 'b"), "2");
+    assert_eq!(eval("get { 1 \"one\" \"k\" 2 } \"k\""), "2");
+    assert_eq!(eval("has { 'a 1 } 'a has { 'a 1 } 'b"), "true false");
+    match error("get { 'a 1 } 'c") {
+        StaplError::KeyNotFound { word, .. } => {
+            assert_eq!(word, "get");
+        }
+        other => panic!("expected a missing key, got {:?}", other),
+    }
+}
+
+#[test]
+fn put_adds_or_replaces_in_place() {
+    assert_eq!(eval("put { 'a 1 } 'b 2"), "{ 'a 1 'b 2 }");
+    assert_eq!(eval("put { 'a 1 'b 2 } 'a 3"), "{ 'a 3 'b 2 }");
+    assert_eq!(eval("put { } 1 '[ 2 ]"), "{ 1 '[ 2 ] }");
+}
+
+#[test]
+fn remove_leaves_the_other_entries() {
+    assert_eq!(eval("remove { 'a 1 'b 2 } 'a"), "{ 'b 2 }");
+    assert_eq!(eval("remove { 'a 1 } 'c"), "{ 'a 1 }");
+}
+
+#[test]
+fn keys_and_values_in_the_order_keys_were_first_put() {
+    assert_eq!(eval("keys { 'b 1 'a 2 }"), "'[ 'b 'a ]");
+    assert_eq!(eval("values { 'b 1 'a 2 }"), "'[ 1 2 ]");
+    assert_eq!(eval("keys put { 'b 1 'a 2 } 'b 3"), "'[ 'b 'a ]");
+    assert_eq!(eval("keys { } values { }"), "'[ ] '[ ]");
+}
+
+#[test]
+fn maps_are_equal_whatever_the_order() {
+    assert_eq!(eval("= { 'a 1 'b 2 } { 'b 2 'a 1 }"), "true");
+    assert_eq!(eval("= { 'a 1 } { 'a 2 }"), "false");
+    assert_eq!(eval("= { 'a 1 } { 'a 1 'b 2 }"), "false");
+    assert_eq!(eval("= { 'a 1 'a 2 } { 'a 2 }"), "true");
+}
+
+#[test]
+fn keys_must_be_literals_numbers_or_strings() {
+    match error("{ '[ 1 ] 2 }") {
+        StaplError::TypeMismatch { word, .. } => {
+            assert_eq!(word, "map");
+        }
+        other => panic!("expected a bad key, got {:?}", other),
+    }
+    match error("get { 'a 1 } '[ 1 ]") {
+        StaplError::TypeMismatch { word, .. } => {
+            assert_eq!(word, "get");
+        }
+        other => panic!("expected a bad key, got {:?}", other),
+    }
+}
+
+#[test]
+fn a_key_without_a_value_is_an_error() {
+    for (source, key) in &[
+        ("{ 'a }", "'a"),
+        ("{ 1 }", "1"),
+        ("{ 'a 1 'b }", "'b"),
+        ("'[ { 1 } ]", "1"),
+    ] {
+        match error(source) {
+            StaplError::Syntax { message, span } => {
+                assert_eq!(message, format!("map key without a value: {}", key));
+                assert_eq!(&source[span.start..span.end], *key);
+            }
+            other => panic!("expected {} to be a syntax error, got {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn words_in_a_map_literal_are_errors() {
+    for (source, word) in &[("{ 'a + 1 2 }", "+"), ("{ 'f dup }", "dup")] {
+        match error(source) {
+            StaplError::Syntax { message, span } => {
+                assert_eq!(message, format!("word in a map literal: {}", word));
+                assert_eq!(&source[span.start..span.end], *word);
+            }
+            other => panic!("expected {} to be a syntax error, got {:?}", source, other),
+        }
+    }
+    // lambdas and stacks hold words without evaluating them
+    assert_eq!(
+        eval("{ 'f [ + 1 $1 ] 's '[ dup ] }"),
+        "{ 'f [ + 1 $1 ] 's '[ dup ] }"
+    );
+}
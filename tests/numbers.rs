@@ -0,0 +1,98 @@
+use third::{Interpreter, LexItem, Number, StaplError};
+
+mod common;
+
+use common::eval;
+
+fn kind(source: &str) -> &'static str {
+    let mut interpreter = Interpreter::new("test");
+    match interpreter.eval_str(source).unwrap().as_slice() {
+        [LexItem::Num(Number::Int(_), _)] => "int",
+        [LexItem::Num(Number::Big(_), _)] => "big",
+        [LexItem::Num(Number::Ratio(_), _)] => "ratio",
+        [LexItem::Num(Number::Float(_), _)] => "float",
+        other => panic!("{} gave {:?}", source, other),
+    }
+}
+
+#[test]
+fn integers_grow_into_bigints_and_back() {
+    assert_eq!(eval("+ 9223372036854775807 1"), "9223372036854775808");
+    assert_eq!(eval("- -9223372036854775808 1"), "-9223372036854775809");
+    assert_eq!(eval("* 9223372036854775807 2"), "18446744073709551614");
+    assert_eq!(eval("neg -9223372036854775808"), "9223372036854775808");
+    assert_eq!(kind("+ 9223372036854775807 1"), "big");
+    assert_eq!(kind("- * 9223372036854775807 2 9223372036854775807"), "int");
+    assert_eq!(eval("/ -9223372036854775808 -1"), "9223372036854775808");
+}
+
+#[test]
+fn inexact_division_gives_rationals() {
+    assert_eq!(eval("/ 7 2"), "7/2");
+    assert_eq!(kind("/ 7 2"), "ratio");
+    assert_eq!(kind("/ 6 3"), "int");
+    assert_eq!(eval("+ 1/3 1/6"), "1/2");
+    assert_eq!(eval("* 3 / 1 3"), "1");
+    assert_eq!(kind("+ 1/2 1/2"), "int");
+}
+
+#[test]
+fn floats_are_contagious() {
+    assert_eq!(eval("+ 1 0.5"), "1.5");
+    assert_eq!(eval("* 2 1.5"), "3.0");
+    assert_eq!(kind("+ 1 1.0"), "float");
+    assert_eq!(kind("+ 1/2 0.5"), "float");
+    assert_eq!(kind("+ 100000000000000000000 1.0"), "float");
+    assert_eq!(eval("= 1 1.0"), "true");
+    assert_eq!(eval("= 1/2 0.5"), "true");
+}
+
+#[test]
+fn literals_in_other_bases_and_with_underscores() {
+    assert_eq!(
+        eval("0x1F 0o17 0b1010 1_000_000 -0x10"),
+        "31 15 10 1000000 -16"
+    );
+    assert_eq!(eval("0xFFFFFFFFFFFFFFFFFF"), "4722366482869645213695");
+    assert_eq!(eval("3.25 1e3 2.5e-1 .5 -.5"), "3.25 1000.0 0.25 0.5 -0.5");
+    assert_eq!(eval("str->num \"0x10\" str->num \" .25 \""), "16 0.25");
+    for source in &["1_", "0x", "0b12"] {
+        match Interpreter::new("test").eval_str(source) {
+            Err(StaplError::UnknownWord { .. }) => {}
+            other => panic!("expected {} not to be a number, got {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn the_remainder_takes_the_sign_of_the_dividend() {
+    assert_eq!(eval("mod 7 3"), "1");
+    assert_eq!(eval("mod -7 3"), "-1");
+    assert_eq!(eval("mod 7 -3"), "1");
+    assert_eq!(eval("mod -7 -3"), "-1");
+    assert_eq!(eval("mod -9223372036854775808 -1"), "0");
+    assert_eq!(eval("mod 1/2 1/3"), "1/6");
+    assert_eq!(eval("mod 7.5 2"), "1.5");
+}
+
+#[test]
+fn floats_must_be_finite() {
+    assert!(Number::parse("1e400").is_none());
+    assert!(Number::parse("-1e400").is_none());
+    match Interpreter::new("test").eval_str("1e400") {
+        Err(StaplError::Syntax { message, .. }) => {
+            assert_eq!(message, "number out of range: 1e400");
+        }
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+    match Interpreter::new("test").eval_str("str->num \"1e400\"") {
+        Err(StaplError::TypeMismatch { word, .. }) => {
+            assert_eq!(word, "str->num");
+        }
+        other => panic!("expected str->num to fail, got {:?}", other),
+    }
+    match Interpreter::new("test").eval_str("* 1e300 1e300") {
+        Err(StaplError::Overflow { .. }) => {}
+        other => panic!("expected an overflow, got {:?}", other),
+    }
+}
@@ -0,0 +1,148 @@
+use proptest::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+use third::{
+    format_source, format_values, parse, Entries, Interpreter, LexItem, Number, Span, TraceEvent,
+    TraceLevel, Value,
+};
+
+fn span() -> Span {
+    return Span::default();
+}
+
+fn word_name() -> impl Strategy<Value = String> {
+    return "[a-z][a-z0-9_?-]{0,6}".prop_filter("keywords are read as values", |w| {
+        !["true", "false", "nil"].contains(&w.as_str())
+    });
+}
+
+fn number() -> impl Strategy<Value = Number> {
+    return prop_oneof![
+        any::<i64>().prop_map(Number::from),
+        "-?[1-9][0-9]{19,30}".prop_map(|n| Number::parse(&n).unwrap()),
+        (any::<i32>(), 2..1000i32)
+            .prop_map(|(n, d)| Number::parse(&format!("{}/{}", n, d)).unwrap()),
+        any::<f64>()
+            .prop_filter("only finite floats can be read back", |x| x.is_finite())
+            .prop_map(Number::Float),
+    ];
+}
+
+fn key() -> impl Strategy<Value = Value> {
+    return prop_oneof![
+        number().prop_map(|n| LexItem::Num(n, span())),
+        any::<String>().prop_map(|s| LexItem::Str(s, span())),
+        word_name().prop_map(|l| LexItem::Literal(l, span())),
+    ];
+}
+
+fn leaf() -> impl Strategy<Value = Value> {
+    return prop_oneof![
+        word_name().prop_map(|w| LexItem::Word(w, span())),
+        number().prop_map(|n| LexItem::Num(n, span())),
+        any::<String>().prop_map(|s| LexItem::Str(s, span())),
+        prop_oneof![
+            word_name(),
+            Just("true".to_string()),
+            Just("nil".to_string())
+        ]
+        .prop_map(|l| LexItem::Literal(l, span())),
+        "[a-z_][a-z0-9_]{0,4}|[0-9]|[1-9][0-9]".prop_map(|p| LexItem::Parameter(p, span())),
+        any::<bool>().prop_map(|b| LexItem::Bool(b, span())),
+        Just(LexItem::Nil(span())),
+    ];
+}
+
+// bodies are stored last item first
+fn stored(mut items: Vec<Value>) -> Vec<Value> {
+    items.reverse();
+    return items;
+}
+
+fn value() -> impl Strategy<Value = Value> {
+    return leaf().prop_recursive(4, 48, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6)
+                .prop_map(|items| LexItem::Stack(stored(items), span())),
+            prop::collection::vec(inner.clone(), 0..6)
+                .prop_map(|items| LexItem::Lambda(stored(items), span())),
+            prop::collection::vec(
+                (
+                    key(),
+                    inner.prop_filter("a map literal can't hold words", |v| {
+                        !matches!(v, LexItem::Word(..))
+                    })
+                ),
+                0..4
+            )
+            .prop_map(|pairs| {
+                let mut entries = Entries::default();
+                for (k, v) in pairs {
+                    entries.put(k, v);
+                }
+                return LexItem::Map(entries, span());
+            }),
+        ]
+    });
+}
+
+proptest! {
+    #[test]
+    fn a_value_reads_back_as_itself(v in value()) {
+        let written = format_source(&v);
+        prop_assert_eq!(parse(&written).unwrap(), vec![v], "{}", written);
+    }
+
+    #[test]
+    fn the_canonical_form_is_stable(v in value()) {
+        let written = format_source(&v);
+        let again: Vec<String> = parse(&written).unwrap().iter().map(format_source).collect();
+        prop_assert_eq!(again, vec![written]);
+    }
+
+    #[test]
+    fn values_read_back_in_order(values in prop::collection::vec(value(), 0..6)) {
+        let written = format_values(&values);
+        prop_assert_eq!(parse(&written).unwrap(), values, "{}", written);
+    }
+}
+
+#[test]
+fn strings_are_escaped() {
+    let s = LexItem::Str("say \"hi\"\\\n\t\r\0".to_string(), span());
+    assert_eq!(format_source(&s), "\"say \\\"hi\\\"\\\\\\n\\t\\r\\0\"");
+}
+
+#[test]
+fn results_are_written_oldest_first() {
+    let mut interpreter = Interpreter::new("test");
+    let values = interpreter
+        .eval_str("1 \"two\" '[ 3 'four ] { 'k true }")
+        .unwrap();
+    assert_eq!(format_values(&values), "1 \"two\" '[ 3 'four ] { 'k true }");
+}
+
+#[test]
+fn traces_write_values_in_their_canonical_form() {
+    let mut interpreter = Interpreter::new("test");
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let sink = events.clone();
+    interpreter.set_trace_level(TraceLevel::Full);
+    interpreter.set_trace_sink(move |event: &TraceEvent| {
+        sink.borrow_mut().push((event.to_text(), event.to_json()));
+    });
+    interpreter
+        .eval_str("define f [ \"a b\" '[ 1 $1 ] ] f 2")
+        .unwrap();
+    let events = events.borrow();
+    let text: Vec<&str> = events.iter().map(|(t, _)| t.as_str()).collect();
+    assert!(
+        text.contains(&"substitute f -> \"a b\" '[ 1 2 ]"),
+        "{:?}",
+        text
+    );
+    assert!(text.contains(&"output \"a b\""), "{:?}", text);
+    assert!(text.contains(&"apply define -> "), "{:?}", text);
+    assert!(events.iter().any(|(_, j)| j
+        == "{\"event\":\"substituted\",\"word\":\"f\",\"body\":\"\\\"a b\\\" '[ 1 2 ]\"}"));
+}
@@ -0,0 +1,146 @@
+use third::{EvalLimits, Interpreter, LexItem, Limit, Number, Span, StaplError, Value};
+
+const FACT: &str = "fact [ if = $1 0 [ 1 ] [ * $1 fact - $1 1 ] ]";
+const FIB: &str = "fib [ if < $1 2 [ $1 ] [ + fib - $1 1 fib - $1 2 ] ]";
+const SUM: &str = "sum [ if = $1 0 [ 0 ] [ + $1 sum - $1 1 ] ]";
+
+fn num(n: i64) -> Value {
+    return LexItem::Num(Number::from(n), Span::default());
+}
+
+fn run(interpreter: &mut Interpreter, source: &str) -> Result<Vec<Value>, StaplError> {
+    return interpreter.eval_str(source);
+}
+
+#[test]
+fn factorial() {
+    let mut interpreter = Interpreter::new("test");
+    run(&mut interpreter, FACT).unwrap();
+    assert_eq!(run(&mut interpreter, "fact 0").unwrap(), vec![num(1)]);
+    assert_eq!(run(&mut interpreter, "fact 5").unwrap(), vec![num(120)]);
+    assert_eq!(
+        run(&mut interpreter, "fact 20").unwrap(),
+        vec![num(2432902008176640000)]
+    );
+}
+
+#[test]
+fn fibonacci() {
+    let mut interpreter = Interpreter::new("test");
+    run(&mut interpreter, FIB).unwrap();
+    assert_eq!(run(&mut interpreter, "fib 1").unwrap(), vec![num(1)]);
+    assert_eq!(run(&mut interpreter, "fib 20").unwrap(), vec![num(6765)]);
+}
+
+#[test]
+fn untaken_branch_is_not_evaluated() {
+    let mut interpreter = Interpreter::new("test");
+    assert_eq!(
+        run(&mut interpreter, "if 1 [ 7 ] [ / 1 0 ]").unwrap(),
+        vec![num(7)]
+    );
+    assert_eq!(
+        run(&mut interpreter, "if 0 [ / 1 0 ] [ 8 ]").unwrap(),
+        vec![num(8)]
+    );
+}
+
+#[test]
+fn deep_recursion_within_default_limits() {
+    let mut interpreter = Interpreter::new("test");
+    run(&mut interpreter, SUM).unwrap();
+    assert_eq!(
+        run(&mut interpreter, "sum 5000").unwrap(),
+        vec![num(12502500)]
+    );
+}
+
+#[test]
+fn recursion_deeper_than_the_call_depth_limit_stops() {
+    let mut interpreter = Interpreter::new("test");
+    let mut limits = EvalLimits::default();
+    limits.max_call_depth = Some(100);
+    interpreter.set_limits(limits);
+    run(&mut interpreter, SUM).unwrap();
+    assert_eq!(run(&mut interpreter, "sum 40").unwrap(), vec![num(820)]);
+    match run(&mut interpreter, "sum 1000") {
+        Err(StaplError::LimitExceeded { limit, .. }) => {
+            assert_eq!(limit, Limit::CallDepth(100));
+        }
+        other => panic!("expected the call depth limit, got {:?}", other),
+    }
+    // resuming would only go over the limit again
+    assert!(!interpreter.is_suspended());
+    assert_eq!(run(&mut interpreter, "sum 10").unwrap(), vec![num(55)]);
+}
+
+#[test]
+fn endless_recursion_runs_into_the_step_limit() {
+    let mut interpreter = Interpreter::new("test");
+    let mut limits = EvalLimits::default();
+    limits.max_steps = Some(10_000);
+    interpreter.set_limits(limits);
+    run(&mut interpreter, "forever [ + 1 forever ]").unwrap();
+    match run(&mut interpreter, "forever") {
+        Err(StaplError::LimitExceeded { .. }) => {}
+        other => panic!("expected a limit to stop the evaluation, got {:?}", other),
+    }
+}
+
+#[test]
+fn resume_carries_on_after_the_step_limit() {
+    let mut interpreter = Interpreter::new("test");
+    let mut limits = EvalLimits::default();
+    limits.max_steps = Some(1_000);
+    interpreter.set_limits(limits);
+    run(&mut interpreter, SUM).unwrap();
+    let mut result = run(&mut interpreter, "sum 500");
+    let mut stops = 0;
+    while let Err(StaplError::LimitExceeded { limit, .. }) = result {
+        assert_eq!(limit, Limit::Steps(1_000));
+        assert!(interpreter.is_suspended());
+        stops = stops + 1;
+        result = interpreter.resume();
+    }
+    assert!(stops > 1);
+    assert_eq!(result.unwrap(), vec![num(125250)]);
+    assert!(!interpreter.is_suspended());
+}
+
+#[test]
+fn the_item_limit_can_not_be_resumed() {
+    let mut interpreter = Interpreter::new("test");
+    let mut limits = EvalLimits::default();
+    limits.max_stack_items = Some(100);
+    interpreter.set_limits(limits);
+    match run(&mut interpreter, &"1 ".repeat(200)) {
+        Err(StaplError::LimitExceeded { limit, .. }) => {
+            assert_eq!(limit, Limit::StackItems(100));
+        }
+        other => panic!("expected the item limit, got {:?}", other),
+    }
+    assert!(!interpreter.is_suspended());
+    assert_eq!(run(&mut interpreter, "+ 1 2").unwrap(), vec![num(3)]);
+}
+
+#[test]
+fn builtins_check_the_item_limit() {
+    let mut interpreter = Interpreter::new("test");
+    let mut limits = EvalLimits::default();
+    limits.max_stack_items = Some(100);
+    interpreter.set_limits(limits);
+    for source in &["take 10000000000 1", "concat range 0 60 range 0 60"] {
+        match run(&mut interpreter, source) {
+            Err(StaplError::LimitExceeded { limit, .. }) => {
+                assert_eq!(limit, Limit::StackItems(100));
+            }
+            other => panic!("expected the item limit for {}, got {:?}", source, other),
+        }
+        // the builtin had taken its arguments, so there is nothing to resume
+        assert!(!interpreter.is_suspended());
+    }
+    assert_eq!(
+        run(&mut interpreter, "len range 0 60").unwrap(),
+        vec![num(60)]
+    );
+}
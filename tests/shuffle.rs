@@ -0,0 +1,72 @@
+use third::{Interpreter, StaplError};
+
+mod common;
+
+use common::eval;
+
+fn underflows(source: &str) -> bool {
+    let mut interpreter = Interpreter::new("test");
+    match interpreter.eval_str(source) {
+        Err(StaplError::StackUnderflow { .. }) => {
+            return true;
+        }
+        _ => {
+            return false;
+        }
+    }
+}
+
+#[test]
+fn words_at_top_level_use_the_output_stack() {
+    assert_eq!(eval("1 dup"), "1 1");
+    assert_eq!(eval("1 2 drop"), "1");
+    assert_eq!(eval("1 2 swap"), "2 1");
+    assert_eq!(eval("1 2 over"), "1 2 1");
+    assert_eq!(eval("1 2 3 rot"), "2 3 1");
+    assert_eq!(eval("1 2 3 pick 2"), "1 2 3 1");
+    assert_eq!(eval("1 2 depth"), "1 2 2");
+    assert_eq!(eval("1 2 clear"), "");
+}
+
+#[test]
+fn words_inside_a_call_use_its_arguments() {
+    assert_eq!(eval("take 3 1 dup 2"), "'[ 1 1 2 ]");
+    assert_eq!(eval("take 2 1 drop 2 3"), "'[ 2 3 ]");
+    assert_eq!(eval("take 3 1 2 swap 3"), "'[ 2 1 3 ]");
+    assert_eq!(eval("take 4 1 2 over 3"), "'[ 1 2 1 3 ]");
+    assert_eq!(eval("take 5 1 2 3 rot 4 5"), "'[ 2 3 1 4 5 ]");
+    assert_eq!(eval("take 3 1 2 pick 1"), "'[ 1 2 1 ]");
+    assert_eq!(eval("take 3 1 2 depth"), "'[ 1 2 2 ]");
+    assert_eq!(eval("take 2 1 2 clear 3 4"), "3 4");
+    assert_eq!(eval("take 2 5 clear 6 7"), "'[ 6 7 ]");
+    assert_eq!(eval("+ 1 dup"), "2");
+}
+
+#[test]
+fn arguments_taken_by_an_earlier_application_are_not_on_the_stack() {
+    // take has used its count, and map keeps its lambda and stacks as arguments
+    assert!(underflows("take 1 drop 7"));
+    assert_eq!(eval("take 2 clear 5 6"), "'[ 5 6 ]");
+    assert_eq!(eval("take 2 depth 7"), "'[ 0 7 ]");
+    assert!(underflows("map [ dup $1 ] '[ 1 2 ]"));
+    assert!(underflows("fold [ swap $1 $2 ] 0 '[ 1 ]"));
+    assert_eq!(eval("filter [ = depth $1 ] '[ 0 1 ]"), "'[ 0 ]");
+}
+
+#[test]
+fn too_few_values_is_an_error() {
+    assert!(underflows("dup"));
+    assert!(underflows("drop"));
+    assert!(underflows("1 swap"));
+    assert!(underflows("1 over"));
+    assert!(underflows("1 2 rot"));
+    assert!(underflows("take 2 swap 1 2"));
+    assert!(underflows("take 3 1 2 rot 3"));
+    let mut interpreter = Interpreter::new("test");
+    match interpreter.eval_str("take 2 1 pick 1") {
+        Err(StaplError::IndexOutOfRange { index, len, .. }) => {
+            assert_eq!((index, len), (1, 1));
+        }
+        other => panic!("expected pick to be out of range, got {:?}", other),
+    }
+}
@@ -0,0 +1,143 @@
+use third::Interpreter;
+
+mod common;
+
+use common::eval_each;
+
+const APPLY: &str = "define apply [ $f $x ]";
+
+#[test]
+fn named_parameters_belong_to_the_outermost_lambda() {
+    assert_eq!(
+        eval_each(&["define dbl [ map [ * $1 2 ] $xs ]", "dbl '[ 1 2 ]"]),
+        "'[ 2 4 ]"
+    );
+    assert_eq!(
+        eval_each(&[
+            "define addall [ map [ + $1 $k ] $xs ]",
+            "addall 10 '[ 1 2 ]"
+        ]),
+        "'[ 11 12 ]"
+    );
+    assert_eq!(
+        eval_each(&[
+            "define scale [ let $f $k [ map [ * $1 $f ] $xs ] ]",
+            "scale 3 '[ 1 2 ]"
+        ]),
+        "'[ 3 6 ]"
+    );
+    assert_eq!(
+        eval_each(&[
+            "define fact [ if = $n 0 [ 1 ] [ * $n fact - $n 1 ] ]",
+            "fact 5"
+        ]),
+        "120"
+    );
+}
+
+#[test]
+fn a_word_takes_the_names_only_its_branches_use() {
+    let f = "define f [ if $c [ $x ] [ 0 ] ]";
+    assert_eq!(eval_each(&[f, "f 0 5"]), "0");
+    assert_eq!(eval_each(&[f, "f 1 5"]), "5");
+}
+
+#[test]
+fn the_branches_of_an_if_use_the_parameters_of_the_word() {
+    assert_eq!(
+        eval_each(&["define h [ if $1 [ + $2 1 ] [ 0 ] ]", "h 1 5 h 0 5"]),
+        "6 0"
+    );
+    assert_eq!(
+        eval_each(&[
+            "define fact [ if = $1 0 [ 1 ] [ * $1 fact - $1 1 ] ]",
+            "fact 5"
+        ]),
+        "120"
+    );
+    // a lambda in a branch still has its own
+    assert_eq!(
+        eval_each(&[
+            "define g [ if $1 [ map [ * $1 $k ] '[ 1 2 ] ] [ 0 ] ]",
+            "g 1 3"
+        ]),
+        "'[ 3 6 ]"
+    );
+}
+
+#[test]
+fn let_bodies_use_the_parameters_of_the_word() {
+    assert_eq!(
+        eval_each(&["define area [ let $w 3 [ * $w $h ] ]", "area 4"]),
+        "12"
+    );
+    assert_eq!(
+        eval_each(&["define s [ let $x $x [ + $x 1 ] ]", "s 4"]),
+        "5"
+    );
+}
+
+#[test]
+fn inner_lets_shadow_outer_ones() {
+    assert_eq!(
+        eval_each(&["define k [ let $x 1 [ let $x 2 [ $x ] ] ]", "k"]),
+        "2"
+    );
+    assert_eq!(
+        eval_each(&["define k [ let $x 1 [ + $x let $x 2 [ * $x 10 ] ] ]", "k"]),
+        "21"
+    );
+    assert_eq!(
+        eval_each(&["define k [ let $x 1 [ '[ $x let $x 2 [ $x ] $x ] ] ]", "k"]),
+        "'[ 1 let $x 2 [ $x ] 1 ]"
+    );
+}
+
+#[test]
+fn a_let_does_not_capture_names_free_in_an_argument() {
+    let f = "define f [ let $y 1 [ '[ $x $y ] ] ]";
+    assert_eq!(eval_each(&[f, "f 7"]), "'[ 7 1 ]");
+    assert_eq!(eval_each(&[f, "f '[ $y ]"]), "'[ '[ $y ] 1 ]");
+    // the binder is renamed to a name that is free nowhere
+    assert_eq!(eval_each(&[f, "f '[ $y $y_1 ]"]), "'[ '[ $y $y_1 ] 1 ]");
+}
+
+#[test]
+fn currying_through_a_returned_closure() {
+    let adder = "define adder [ let $k $n [ [ + $k $1 ] ] ]";
+    assert_eq!(
+        eval_each(&[APPLY, adder, "apply adder 5 10 apply adder 1 10"]),
+        "15 11"
+    );
+    assert_eq!(
+        eval_each(&[APPLY, adder, "fold [ apply $1 $2 ] adder 100 '[ 1 ]"]),
+        "101"
+    );
+    assert_eq!(
+        eval_each(&[
+            APPLY,
+            "define adder [ if = $n 0 [ [ $1 ] ] [ [ + $n $1 ] ] ]",
+            "apply adder 5 10"
+        ]),
+        "15"
+    );
+}
+
+#[test]
+fn a_returned_lambda_closes_over_the_parameters_of_the_word() {
+    let adder = "define adder [ [ + $n $1 ] ]";
+    assert_eq!(eval_each(&[adder, "adder 5 10"]), "15");
+    assert_eq!(eval_each(&[adder, "map adder 5 '[ 1 2 ]"]), "'[ 6 7 ]");
+    assert_eq!(
+        eval_each(&[APPLY, adder, "apply adder 5 10 apply adder 1 10"]),
+        "15 11"
+    );
+    // the captured name is bound, the closure only takes $1
+    let mut interpreter = Interpreter::new("test");
+    interpreter.eval_str(adder).unwrap();
+    let error = interpreter.eval_str("adder 5").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "arity error in lambda: expected 1 arguments, found 0"
+    );
+}
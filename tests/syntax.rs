@@ -0,0 +1,145 @@
+use third::{format_values, is_balanced, Interpreter, StaplError};
+
+// the message of the syntax error and the source its span covers
+fn syntax_error(source: &str) -> (String, String) {
+    let mut interpreter = Interpreter::new("test");
+    match interpreter.eval_str(source) {
+        Err(StaplError::Syntax { message, span }) => {
+            return (message, source[span.start..span.end].to_string());
+        }
+        other => panic!("expected {} to be a syntax error, got {:?}", source, other),
+    }
+}
+
+#[test]
+fn escapes_in_strings() {
+    let mut interpreter = Interpreter::new("test");
+    let values = interpreter
+        .eval_str(r#""\u{41}\n\t\"\\" "\u{1F600}""#)
+        .unwrap();
+    assert_eq!(format_values(&values), r#""A\n\t\"\\" "😀""#);
+}
+
+#[test]
+fn unterminated_strings_are_errors() {
+    assert_eq!(
+        syntax_error("\"abc"),
+        ("unterminated string".to_string(), "\"abc".to_string())
+    );
+    assert_eq!(syntax_error("1 \"abc\\\"").1, "\"abc\\\"");
+}
+
+#[test]
+fn unknown_escapes_are_errors() {
+    assert_eq!(
+        syntax_error(r#""a\qb""#),
+        ("unknown escape \\q".to_string(), "\\q".to_string())
+    );
+}
+
+#[test]
+fn malformed_unicode_escapes_are_errors() {
+    for source in &[
+        r#""\u{zz}""#,
+        r#""\u41""#,
+        r#""\u{110000}""#,
+        r#""\u{}""#,
+        r#""\u{1234567}""#,
+    ] {
+        let (message, written) = syntax_error(source);
+        assert!(
+            message.starts_with("malformed unicode escape"),
+            "{}",
+            message
+        );
+        assert!(written.starts_with("\\u"), "{}", written);
+    }
+}
+
+#[test]
+fn nothing_is_evaluated_after_a_syntax_error() {
+    let mut interpreter = Interpreter::new("test");
+    assert!(interpreter.eval_str("define x [ 1 ] \"\\q\"").is_err());
+    assert!(interpreter.eval_str("x").is_err());
+}
+
+#[test]
+fn unexpected_characters_are_errors() {
+    assert_eq!(
+        syntax_error("1 % 2"),
+        ("unexpected character '%'".to_string(), "%".to_string())
+    );
+    assert_eq!(syntax_error("1 ; 2").1, ";");
+    assert_eq!(
+        syntax_error(". 1"),
+        ("unexpected character '.'".to_string(), ".".to_string())
+    );
+    let mut interpreter = Interpreter::new("test");
+    assert_eq!(
+        format_values(&interpreter.eval_str("+ .5 1").unwrap()),
+        "1.5"
+    );
+}
+
+#[test]
+fn brackets_must_be_matched() {
+    assert_eq!(
+        syntax_error("1 ] + 2 3"),
+        ("unmatched ]".to_string(), "]".to_string())
+    );
+    assert_eq!(
+        syntax_error("[ 1 ] }"),
+        ("unmatched }".to_string(), "}".to_string())
+    );
+    assert_eq!(
+        syntax_error("+ 1 [ 2"),
+        ("unclosed [".to_string(), "[".to_string())
+    );
+    assert_eq!(
+        syntax_error("{ 'a [ 1 ]"),
+        ("unclosed {".to_string(), "{".to_string())
+    );
+}
+
+#[test]
+fn brackets_must_close_with_their_own_kind() {
+    assert_eq!(
+        syntax_error("{ 1 ] }"),
+        ("] closes {".to_string(), "]".to_string())
+    );
+    assert_eq!(
+        syntax_error("[ 1 }"),
+        ("} closes [".to_string(), "}".to_string())
+    );
+    assert_eq!(syntax_error("'[ { 'a 1 ] ]").0, "] closes {");
+}
+
+#[test]
+fn input_is_balanced_once_brackets_and_strings_are_closed() {
+    for input in &[
+        "",
+        "1 2",
+        "[ 1 ]",
+        "{ 'a [ 1 ] }",
+        "\"[\"",
+        "\"a\\\"b\"",
+        "[ \"]\" ]\n",
+    ] {
+        assert!(is_balanced(input), "{}", input);
+    }
+    for input in &[
+        "[",
+        "{ 'a",
+        "[ [ 1 ]",
+        "\"abc",
+        "\"abc\\\"",
+        "[ \"]\"",
+        "'[ 1\n2",
+    ] {
+        assert!(!is_balanced(input), "{}", input);
+    }
+    // more input can't fix a closing bracket nothing opened, the parser reports it
+    for input in &["]", "1 } [", "] [ ["] {
+        assert!(is_balanced(input), "{}", input);
+    }
+}
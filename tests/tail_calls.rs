@@ -0,0 +1,54 @@
+use third::{EvalLimits, Interpreter, LexItem, Limit, Number, Span, StaplError, Value};
+
+// the recursive call is the last thing the chosen branch does
+const COUNT: &str = "count [ if = $1 0 [ $2 ] [ count - $1 1 + $2 1 ] ]";
+// the recursive call's result is still needed by +
+const SUM: &str = "sum [ if = $1 0 [ 0 ] [ + $1 sum - $1 1 ] ]";
+
+fn num(n: i64) -> Value {
+    return LexItem::Num(Number::from(n), Span::default());
+}
+
+// only enough call depth for the word, if and one argument being worked out
+fn shallow_interpreter() -> Interpreter {
+    let mut interpreter = Interpreter::new("test");
+    let mut limits = EvalLimits::default();
+    limits.max_call_depth = Some(2);
+    limits.max_steps = None;
+    interpreter.set_limits(limits);
+    return interpreter;
+}
+
+#[test]
+fn tail_recursion_runs_in_constant_call_depth() {
+    let mut interpreter = shallow_interpreter();
+    interpreter.eval_str(COUNT).unwrap();
+    assert_eq!(interpreter.eval_str("count 3 0").unwrap(), vec![num(3)]);
+    assert_eq!(
+        interpreter.eval_str("count 20000 0").unwrap(),
+        vec![num(20000)]
+    );
+}
+
+#[test]
+fn chosen_branch_does_not_take_a_frame() {
+    let mut interpreter = shallow_interpreter();
+    assert_eq!(
+        interpreter
+            .eval_str("if 1 [ if 1 [ + 1 2 ] [ 0 ] ] [ 0 ]")
+            .unwrap(),
+        vec![num(3)]
+    );
+}
+
+#[test]
+fn non_tail_recursion_still_needs_a_frame_per_level() {
+    let mut interpreter = shallow_interpreter();
+    interpreter.eval_str(SUM).unwrap();
+    match interpreter.eval_str("sum 100") {
+        Err(StaplError::LimitExceeded { limit, .. }) => {
+            assert_eq!(limit, Limit::CallDepth(2));
+        }
+        other => panic!("expected the call depth limit, got {:?}", other),
+    }
+}
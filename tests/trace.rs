@@ -0,0 +1,147 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use third::{Interpreter, LexItem, Number, Span, TraceEvent, TraceLevel};
+
+mod common;
+
+use common::third;
+
+const INC: &str = "define inc [ + $1 1 ] inc 2 3";
+
+// the events an evaluation reports at a level, in text form. the time taken is left out
+fn trace(level: TraceLevel, source: &str) -> Vec<String> {
+    let mut interpreter = Interpreter::new("test");
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let sink = events.clone();
+    interpreter.set_trace_level(level);
+    interpreter.set_trace_sink(move |event: &TraceEvent| {
+        sink.borrow_mut().push(event.clone());
+    });
+    let _ = interpreter.eval_str(source);
+    let events = events.borrow();
+    return events
+        .iter()
+        .map(|e| match e {
+            TraceEvent::Finished { elapsed, .. } => {
+                e.to_text().replace(&format!(" {:?},", elapsed), "")
+            }
+            _ => e.to_text(),
+        })
+        .collect();
+}
+
+#[test]
+fn each_level_adds_to_the_one_before() {
+    assert_eq!(trace(TraceLevel::Off, INC), Vec::<String>::new());
+    assert_eq!(
+        trace(TraceLevel::Summary, INC),
+        vec!["finished in 13 steps, 2 outputs"]
+    );
+    assert_eq!(
+        trace(TraceLevel::Steps, INC),
+        vec![
+            "[1] push define at test:1:1 expecting Word Any",
+            "apply define -> ",
+            "[1] push inc at test:1:23 expecting Value",
+            "substitute inc -> + 2 1",
+            "[1] push + at test:1:14 expecting Num Num",
+            "apply + -> 3",
+            "finished in 13 steps, 2 outputs",
+        ]
+    );
+    assert_eq!(
+        trace(TraceLevel::Full, INC),
+        vec![
+            "[1] push define at test:1:1 expecting Word Any",
+            "bind define $1 = inc",
+            "bind define $2 = [ + $1 1 ]",
+            "apply define -> ",
+            "[1] push inc at test:1:23 expecting Value",
+            "bind inc $1 = 2",
+            "substitute inc -> + 2 1",
+            "[1] push + at test:1:14 expecting Num Num",
+            "bind + $1 = 2",
+            "bind + $2 = 1",
+            "apply + -> 3",
+            "output 3",
+            "output 3",
+            "finished in 13 steps, 2 outputs",
+        ]
+    );
+}
+
+#[test]
+fn the_summary_reports_an_error() {
+    assert_eq!(
+        trace(TraceLevel::Summary, "/ 1 0"),
+        vec!["finished in 4 steps, 0 outputs, error: division by zero in /"]
+    );
+}
+
+#[test]
+fn events_as_json() {
+    let pushed = TraceEvent::CallPushed {
+        word: "+".to_string(),
+        depth: 1,
+        expectations: Vec::new(),
+        span: Span::default(),
+    };
+    assert_eq!(pushed.to_json(), format!("{{\"event\":\"call_pushed\",\"word\":\"+\",\"depth\":1,\"expects\":\"\",\"at\":\"{}\"}}", Span::default()));
+    let output = TraceEvent::Output {
+        value: LexItem::Str("a \"b\"\n\u{1}".to_string(), Span::default()),
+    };
+    assert_eq!(
+        output.to_json(),
+        r#"{"event":"output","value":"\"a \\\"b\\\"\\n\u0001\""}"#
+    );
+    let applied = TraceEvent::Applied {
+        word: "+".to_string(),
+        results: vec![LexItem::Num(Number::from(3), Span::default())],
+    };
+    assert_eq!(
+        applied.to_json(),
+        r#"{"event":"applied","word":"+","results":"3"}"#
+    );
+    let finished = TraceEvent::Finished {
+        steps: 4,
+        elapsed: Duration::from_micros(12),
+        outputs: 0,
+        error: Some("division by zero in /".to_string()),
+    };
+    assert_eq!(
+        finished.to_json(),
+        r#"{"event":"finished","steps":4,"elapsed_us":12,"outputs":0,"error":"division by zero in /"}"#
+    );
+}
+
+#[test]
+fn trace_from_the_command_line() {
+    let (stdout, stderr, code) = third(&["--trace", "steps", "-e", "+ 1 2"]);
+    assert_eq!(stdout, "3\n");
+    assert_eq!(code, 0);
+    let lines: Vec<&str> = stderr.lines().collect();
+    assert_eq!(lines[0], "trace: [1] push + at -e:1:1 expecting Num Num");
+    assert_eq!(lines[1], "trace: apply + -> 3");
+    assert!(lines[2].starts_with("trace: finished in "), "{}", stderr);
+    let (_, stderr, _) = third(&[
+        "--trace",
+        "summary",
+        "--trace-format",
+        "json",
+        "-e",
+        "+ 1 2",
+    ]);
+    assert!(
+        stderr.starts_with("{\"event\":\"finished\",\"steps\":"),
+        "{}",
+        stderr
+    );
+    assert_eq!(stderr.lines().count(), 1);
+    let (_, stderr, code) = third(&["--trace", "loud", "-e", "1"]);
+    assert_eq!(
+        stderr,
+        "unknown trace level loud, expected off, summary, steps or full\n"
+    );
+    assert_eq!(code, 2);
+}
@@ -0,0 +1,93 @@
+use third::{Interpreter, LexItem, Span, Value};
+
+fn boolean(b: bool) -> Value {
+    return LexItem::Bool(b, Span::default());
+}
+
+// what if chooses for a condition, as the branch it took
+fn chosen(condition: &str) -> &'static str {
+    let mut interpreter = Interpreter::new("test");
+    let source = format!("if {} \"then\" \"else\"", condition);
+    let result = interpreter.eval_str(&source).unwrap();
+    match result.as_slice() {
+        [LexItem::Str(s, _)] if s == "then" => {
+            return "then";
+        }
+        [LexItem::Str(s, _)] if s == "else" => {
+            return "else";
+        }
+        _ => panic!("if {} gave {:?}", condition, result),
+    }
+}
+
+#[test]
+fn booleans_and_nil() {
+    assert_eq!(chosen("true"), "then");
+    assert_eq!(chosen("false"), "else");
+    assert_eq!(chosen("nil"), "else");
+}
+
+#[test]
+fn numbers_are_false_only_when_zero() {
+    assert_eq!(chosen("0"), "else");
+    assert_eq!(chosen("0.0"), "else");
+    assert_eq!(chosen("1"), "then");
+    assert_eq!(chosen("-3"), "then");
+    assert_eq!(chosen("0.5"), "then");
+    assert_eq!(chosen("1/3"), "then");
+    assert_eq!(chosen("100000000000000000000000"), "then");
+}
+
+#[test]
+fn strings_stacks_and_maps_are_false_only_when_empty() {
+    assert_eq!(chosen("\"\""), "else");
+    assert_eq!(chosen("\"0\""), "then");
+    assert_eq!(chosen("'[ ]"), "else");
+    assert_eq!(chosen("'[ 0 ]"), "then");
+    assert_eq!(chosen("{ }"), "else");
+    assert_eq!(chosen("{ 'a nil }"), "then");
+}
+
+#[test]
+fn literals_are_true() {
+    assert_eq!(chosen("'false"), "then");
+}
+
+#[test]
+fn a_lambda_condition_is_evaluated_first() {
+    assert_eq!(chosen("[ 0 ]"), "else");
+    assert_eq!(chosen("[ \"x\" ]"), "then");
+}
+
+#[test]
+fn comparisons_return_booleans() {
+    let mut interpreter = Interpreter::new("test");
+    let cases = [
+        ("= 1 1", true),
+        ("!= 1 1", false),
+        ("< 1 2", true),
+        (">= 1 2", false),
+        ("= \"a\" \"a\"", true),
+        ("= true true", true),
+        ("= nil false", false),
+        ("= 1 true", false),
+        ("not nil", true),
+        ("and 1 \"\"", false),
+        ("or '[ ] '[ 1 ]", true),
+        ("has { 'a 1 } 'a", true),
+    ];
+    for (source, expected) in cases.iter() {
+        assert_eq!(
+            interpreter.eval_str(source).unwrap(),
+            vec![boolean(*expected)],
+            "{}",
+            source
+        );
+    }
+}
+
+#[test]
+fn booleans_are_not_numbers() {
+    let mut interpreter = Interpreter::new("test");
+    assert!(interpreter.eval_str("+ true 1").is_err());
+}